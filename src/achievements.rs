@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::storage;
+
+const FILE_NAME: &str = "achievements.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Achievement {
+    LongSnake,
+    Survivor,
+    QuickBite,
+    Minimalist,
+    SecretCode,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Self::LongSnake,
+        Self::Survivor,
+        Self::QuickBite,
+        Self::Minimalist,
+        Self::SecretCode,
+    ];
+    const LONG_SNAKE_LENGTH: usize = 20;
+    const SURVIVOR_TICKS: u32 = 500;
+    const QUICK_BITE_TICKS: u32 = 3;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LongSnake => "Long snake",
+            Self::Survivor => "Survivor",
+            Self::QuickBite => "Quick bite",
+            Self::Minimalist => "Minimalist",
+            Self::SecretCode => "Secret code",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::LongSnake => "Reach length 20",
+            Self::Survivor => "Survive 500 ticks",
+            Self::QuickBite => "Eat an apple within 3 ticks of the last one",
+            Self::Minimalist => "Die at length 1",
+            Self::SecretCode => "Enter the Konami code",
+        }
+    }
+
+    /// How the achievement is written to the save file
+    fn key(&self) -> &'static str {
+        match self {
+            Self::LongSnake => "long-snake",
+            Self::Survivor => "survivor",
+            Self::QuickBite => "quick-bite",
+            Self::Minimalist => "minimalist",
+            Self::SecretCode => "secret-code",
+        }
+    }
+}
+
+/// Achievements unlocked so far, over every session
+#[derive(Debug)]
+pub struct Achievements {
+    unlocked: HashSet<Achievement>,
+    /// Set for games that don't count, where nothing can be unlocked
+    locked: bool,
+}
+
+impl Achievements {
+    /// Reads the unlocked achievements, one per line, ignoring any it doesn't know
+    pub fn load() -> Self {
+        let saved = storage::read(FILE_NAME);
+        let unlocked = Achievement::ALL
+            .into_iter()
+            .filter(|achievement| saved.lines().any(|line| line.trim() == achievement.key()))
+            .collect();
+
+        Self {
+            unlocked,
+            locked: false,
+        }
+    }
+
+    /// Achievements that never unlock, for games that don't count
+    pub fn locked() -> Self {
+        Self {
+            unlocked: HashSet::new(),
+            locked: true,
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let lines: String = Achievement::ALL
+            .iter()
+            .filter(|achievement| self.is_unlocked(**achievement))
+            .map(|achievement| format!("{}\n", achievement.key()))
+            .collect();
+
+        storage::write(FILE_NAME, &lines)
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Checks the achievements for a snake of `length` that has been alive for `ticks`
+    pub fn check_move(&mut self, length: usize, ticks: u32) -> Option<Achievement> {
+        self.unlock_if(
+            Achievement::LongSnake,
+            length >= Achievement::LONG_SNAKE_LENGTH,
+        )
+        .or_else(|| self.unlock_if(Achievement::Survivor, ticks >= Achievement::SURVIVOR_TICKS))
+    }
+
+    /// Checks the achievements for an apple eaten `ticks_since_apple` after the last one, if any
+    pub fn check_apple(&mut self, ticks_since_apple: Option<u32>) -> Option<Achievement> {
+        self.unlock_if(
+            Achievement::QuickBite,
+            ticks_since_apple.is_some_and(|ticks| ticks <= Achievement::QUICK_BITE_TICKS),
+        )
+    }
+
+    /// Checks the achievements for the snake dying at `length`
+    pub fn check_death(&mut self, length: usize) -> Option<Achievement> {
+        self.unlock_if(Achievement::Minimalist, length == 1)
+    }
+
+    pub fn check_secret_code(&mut self) -> Option<Achievement> {
+        self.unlock_if(Achievement::SecretCode, true)
+    }
+
+    /// Unlocks `achievement` if `condition` holds and it wasn't already, returning it if so
+    fn unlock_if(&mut self, achievement: Achievement, condition: bool) -> Option<Achievement> {
+        if self.locked || !condition || !self.unlocked.insert(achievement) {
+            return None;
+        }
+        // Failing to save just means it'll have to be unlocked again next time
+        self.save().ok();
+
+        Some(achievement)
+    }
+}
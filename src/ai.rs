@@ -0,0 +1,104 @@
+use crate::Direction;
+
+/// Picks the next direction for a computer controlled snake with its head at `head`, currently
+/// moving in `current`.
+///
+/// `step` returns the cell reached by moving from a cell in a direction, or `None` if that leaves
+/// the board, and `is_blocked` whether running into a cell is fatal. The snake heads greedily for
+/// the nearest apple, only considering moves that don't crash straight away and preferring ones
+/// that don't lead into a dead end. With no safe move left it keeps going straight.
+pub fn choose_direction(
+    head: (u16, u16),
+    current: Direction,
+    apples: &[(u16, u16)],
+    step: impl Fn((u16, u16), Direction) -> Option<(u16, u16)>,
+    is_blocked: impl Fn((u16, u16)) -> bool,
+) -> Direction {
+    let is_safe = |cell: (u16, u16)| cell != head && !is_blocked(cell);
+
+    Direction::ALL
+        .into_iter()
+        .filter(|&direction| direction != current.opposite())
+        .filter_map(|direction| {
+            step(head, direction)
+                .filter(|&cell| is_safe(cell))
+                .map(|cell| (direction, cell))
+        })
+        .min_by_key(|&(direction, cell)| {
+            let dead_end = !Direction::ALL
+                .into_iter()
+                .any(|next| step(cell, next).is_some_and(is_safe));
+            let distance = apples
+                .iter()
+                .map(|apple| apple.0.abs_diff(cell.0) + apple.1.abs_diff(cell.1))
+                .min()
+                .unwrap_or(0);
+
+            // Ties go to carrying on straight so the snake doesn't zigzag
+            (dead_end, distance, direction != current)
+        })
+        .map_or(current, |(direction, _)| direction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: i16 = 10;
+
+    /// A step on an empty board with no wrapping
+    fn step((x, y): (u16, u16), direction: Direction) -> Option<(u16, u16)> {
+        let (dx, dy) = direction.get_vec2();
+        let (x, y) = (x as i16 + dx, y as i16 + dy);
+        ((0..SIZE).contains(&x) && (0..SIZE).contains(&y)).then_some((x as u16, y as u16))
+    }
+
+    #[test]
+    fn heads_straight_for_the_apple() {
+        let choose =
+            |head, current, apple| choose_direction(head, current, &[apple], step, |_| false);
+        assert_eq!(choose((5, 5), Direction::Right, (8, 5)), Direction::Right);
+        assert_eq!(choose((5, 5), Direction::Right, (5, 1)), Direction::Up);
+        // Turning back isn't allowed, and nothing else gets closer
+        assert_eq!(choose((5, 5), Direction::Up, (5, 9)), Direction::Up);
+        // Of two equally good moves, carrying on wins
+        assert_eq!(choose((5, 5), Direction::Down, (8, 8)), Direction::Down);
+    }
+
+    #[test]
+    fn goes_around_walls_and_its_body() {
+        let body = [(6, 5), (6, 4), (5, 4), (4, 4)];
+        let direction = choose_direction((5, 5), Direction::Down, &[(9, 5)], step, |cell| {
+            body.contains(&cell)
+        });
+        assert_eq!(direction, Direction::Down);
+
+        // Off the edge is never chosen, even with the apple on the other side
+        let direction = choose_direction((9, 5), Direction::Right, &[(0, 5)], step, |_| false);
+        assert_ne!(direction, Direction::Right);
+    }
+
+    #[test]
+    fn avoids_dead_ends() {
+        // Up leads into a pocket with no way out, so it goes the long way round
+        let walls = [(4, 3), (6, 3), (5, 2)];
+        let direction = choose_direction((5, 4), Direction::Up, &[(5, 0)], step, |cell| {
+            walls.contains(&cell)
+        });
+        assert_ne!(direction, Direction::Up);
+    }
+
+    #[test]
+    fn keeps_going_when_boxed_in() {
+        let walls = [(4, 5), (6, 5), (5, 4), (5, 6)];
+        let direction = choose_direction((5, 5), Direction::Left, &[(0, 0)], step, |cell| {
+            walls.contains(&cell)
+        });
+        assert_eq!(direction, Direction::Left);
+        // And with nothing to chase
+        assert_eq!(
+            choose_direction((0, 0), Direction::Up, &[], step, |_| true),
+            Direction::Up
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use ratatui::style::Color;
+
+/// An apple on the board, grows the snake when eaten
+#[derive(Debug, Clone)]
+pub struct Apple {
+    pub position: (u16, u16),
+    /// Move ticks since the apple appeared on its current cell
+    pub age: u32,
+    /// Shared by two apples that spawned together, which are eaten together if they're next to
+    /// each other
+    pub pair: Option<u32>,
+    /// Shared by the two apples of a choice, eating either one takes the other away
+    pub choice: Option<u32>,
+    /// The risky apple of a choice, next to a wall and worth more
+    pub premium: bool,
+    /// Where the apple comes in the order they have to be eaten in sequence mode
+    pub color: Option<AppleColor>,
+    /// Left behind by a snake that lost a life, on top of the usual apples
+    pub dropped: bool,
+}
+
+impl Apple {
+    pub fn new(position: (u16, u16)) -> Self {
+        Self {
+            position,
+            age: 0,
+            pair: None,
+            choice: None,
+            premium: false,
+            color: None,
+            dropped: false,
+        }
+    }
+
+    /// Moves the apple to a new cell as if it had just appeared there, keeping its color and
+    /// whether it was dropped
+    pub fn relocate(&mut self, position: (u16, u16)) {
+        *self = Self {
+            color: self.color,
+            dropped: self.dropped,
+            ..Self::new(position)
+        };
+    }
+}
+
+/// The apples in sequence mode, which have to be eaten in this order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppleColor {
+    Red,
+    Yellow,
+    Green,
+}
+
+impl AppleColor {
+    pub const ORDER: [AppleColor; 3] = [Self::Red, Self::Yellow, Self::Green];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::Yellow => "Yellow",
+            Self::Green => "Green",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Red => Color::Red,
+            Self::Yellow => Color::Yellow,
+            Self::Green => Color::Green,
+        }
+    }
+}
+
+/// A big apple covering a square of cells, worth more than a normal one
+#[derive(Debug, Clone)]
+pub struct MegaApple {
+    /// Top left cell of the square
+    pub anchor: (u16, u16),
+    pub size: u16,
+    /// Move ticks until it disappears
+    pub ticks_left: u32,
+}
+
+impl MegaApple {
+    pub fn cells(&self) -> impl Iterator<Item = (u16, u16)> {
+        square(self.anchor, self.size)
+    }
+
+    pub fn contains(&self, (x, y): (u16, u16)) -> bool {
+        (self.anchor.0..self.anchor.0 + self.size).contains(&x)
+            && (self.anchor.1..self.anchor.1 + self.size).contains(&y)
+    }
+}
+
+/// Returns the top left cells of every `size` by `size` square made up only of `free_cells`
+pub fn free_squares(free_cells: &[(u16, u16)], size: u16) -> Vec<(u16, u16)> {
+    let free: HashSet<_> = free_cells.iter().copied().collect();
+
+    free_cells
+        .iter()
+        .copied()
+        .filter(|&anchor| square(anchor, size).all(|cell| free.contains(&cell)))
+        .collect()
+}
+
+fn square((x, y): (u16, u16), size: u16) -> impl Iterator<Item = (u16, u16)> {
+    (x..x + size).flat_map(move |x| (y..y + size).map(move |y| (x, y)))
+}
+
+/// Apples left alone for too long go rotten, and then disappear
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppleState {
+    Fresh,
+    /// Costs score instead of growing the snake
+    Rotten,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: u16 = 6;
+
+    /// Every cell of the board apart from `taken`
+    fn board(taken: &[(u16, u16)]) -> Vec<(u16, u16)> {
+        (0..SIZE)
+            .flat_map(|x| (0..SIZE).map(move |y| (x, y)))
+            .filter(|cell| !taken.contains(cell))
+            .collect()
+    }
+
+    #[test]
+    fn an_empty_board_is_all_squares_short_of_the_far_edges() {
+        let squares = free_squares(&board(&[]), 2);
+        assert_eq!(squares.len(), (SIZE as usize - 1).pow(2));
+        assert!(squares.iter().all(|&(x, y)| x < SIZE - 1 && y < SIZE - 1));
+    }
+
+    #[test]
+    fn a_single_hole_is_found() {
+        let hole = [(2, 3), (3, 3), (2, 4), (3, 4)];
+        let taken: Vec<_> = board(&[])
+            .into_iter()
+            .filter(|cell| !hole.contains(cell))
+            .collect();
+        assert_eq!(free_squares(&board(&taken), 2), [(2, 3)]);
+    }
+
+    #[test]
+    fn a_checkerboard_has_no_squares() {
+        let taken: Vec<_> = board(&[])
+            .into_iter()
+            .filter(|(x, y)| (x + y) % 2 == 0)
+            .collect();
+        assert!(free_squares(&board(&taken), 2).is_empty());
+        assert!(free_squares(&[], 2).is_empty());
+    }
+
+    #[test]
+    fn squares_reach_the_far_edge_and_corner() {
+        let last = SIZE - 1;
+        let corner = [
+            (last - 1, last - 1),
+            (last, last - 1),
+            (last - 1, last),
+            (last, last),
+        ];
+        assert_eq!(free_squares(&corner, 2), [(last - 1, last - 1)]);
+        // A lone column on the edge has nothing to its right to make a square with
+        let edge: Vec<_> = (0..SIZE).map(|y| (last, y)).collect();
+        assert!(free_squares(&edge, 2).is_empty());
+    }
+}
@@ -0,0 +1,32 @@
+use anyhow::Result;
+
+use crate::storage;
+
+const FILE_NAME: &str = "best.txt";
+
+/// The best score saved in the `bucket` of scores for the game's rules, or 0 if there isn't
+/// one or the file can't be made sense of
+pub fn best_score(bucket: &str) -> u32 {
+    storage::read(FILE_NAME)
+        .lines()
+        .find_map(|line| {
+            let (line_bucket, score) = line.rsplit_once(' ')?;
+            (line_bucket == bucket).then(|| score.parse().ok())?
+        })
+        .unwrap_or(0)
+}
+
+/// Saves `score` as the best in `bucket`, replacing whatever was there
+pub fn save_best_score(bucket: &str, score: u32) -> Result<()> {
+    let mut lines: Vec<_> = storage::read(FILE_NAME)
+        .lines()
+        .filter(|line| {
+            line.rsplit_once(' ')
+                .is_none_or(|(line_bucket, _)| line_bucket != bucket)
+        })
+        .map(str::to_string)
+        .collect();
+    lines.push(format!("{bucket} {score}"));
+
+    storage::write(FILE_NAME, &(lines.join("\n") + "\n"))
+}
@@ -0,0 +1,92 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+
+use crate::storage;
+
+const FILE_NAME: &str = "daily.txt";
+
+/// A calendar day in UTC, which everyone playing the daily challenge on it shares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    /// Days since 1970-01-01
+    days: i64,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        Self {
+            days: (seconds / 86_400) as i64,
+        }
+    }
+
+    /// Seed for the day's random number generator
+    pub fn seed(&self) -> u64 {
+        self.days as u64
+    }
+
+    /// Splits the date into a year, month, and day of the month
+    fn civil(&self) -> (i64, u32, u32) {
+        // Counts from 0000-03-01 so leap days fall at the end of each year
+        let days = self.days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        (year, month, day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.civil();
+        write!(f, "{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// The best score saved for `date` in the `bucket` of scores for the game's rules, if it has
+/// been played before
+pub fn best_score(date: Date, bucket: &str) -> Option<u32> {
+    let key = key(date, bucket);
+    storage::read(FILE_NAME).lines().find_map(|line| {
+        let (line_key, score) = line.rsplit_once(' ')?;
+        (line_key == key).then(|| score.parse().ok())?
+    })
+}
+
+/// Saves `score` as the best for `date` in `bucket`, replacing whatever was there
+pub fn save_best_score(date: Date, bucket: &str, score: u32) -> Result<()> {
+    let key = key(date, bucket);
+    let mut lines: Vec<_> = storage::read(FILE_NAME)
+        .lines()
+        .filter(|line| {
+            line.rsplit_once(' ')
+                .is_none_or(|(line_key, _)| line_key != key)
+        })
+        .map(str::to_string)
+        .collect();
+    lines.push(format!("{key} {score}"));
+
+    storage::write(FILE_NAME, &(lines.join("\n") + "\n"))
+}
+
+fn key(date: Date, bucket: &str) -> String {
+    format!("{date} {bucket}")
+}
@@ -0,0 +1,33 @@
+use crate::Direction;
+
+/// Where the dump of the game state is written
+pub const DUMP_FILE_NAME: &str = "debug-dump.txt";
+
+/// State for the cheat keys of a `--debug` game
+#[derive(Debug)]
+pub struct DebugTools {
+    /// The cell apples are spawned on, moved around with Shift+IJKL
+    pub cursor: (u16, u16),
+    /// Nothing the snake runs into can hurt it
+    pub god_mode: bool,
+}
+
+impl DebugTools {
+    pub fn new(cursor: (u16, u16)) -> Self {
+        Self {
+            cursor,
+            god_mode: false,
+        }
+    }
+
+    /// Moves the cursor a cell in `direction`, stopping at the edge of a `width` by `height`
+    /// board
+    pub fn move_cursor(&mut self, direction: Direction, width: u16, height: u16) {
+        let (dx, dy) = direction.get_vec2();
+        let (x, y) = self.cursor;
+        self.cursor = (
+            (x as i16 + dx).clamp(0, width as i16 - 1) as u16,
+            (y as i16 + dy).clamp(0, height as i16 - 1) as u16,
+        );
+    }
+}
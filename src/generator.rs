@@ -0,0 +1,211 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::Rng;
+
+use crate::speed::SpeedCurve;
+
+/// Obstacle arrangements made up fresh for each game, for a square board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generator {
+    /// Rectangles inside each other, each with a couple of gaps to get through
+    Rings,
+    /// A plus through the middle of the board with a gap in each arm
+    Cross,
+    /// Single wall cells scattered over a loose grid
+    Pillars,
+    /// A corridor two cells wide winding back and forth down the board
+    Corridor,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 4] = [Self::Rings, Self::Cross, Self::Pillars, Self::Corridor];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rings => "rings",
+            Self::Cross => "cross",
+            Self::Pillars => "pillars",
+            Self::Corridor => "corridor",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|generator| generator.name() == name)
+    }
+
+    /// Where the snake starts, facing right, or `None` for the middle of the board
+    pub fn spawn(&self) -> Option<(u16, u16)> {
+        match self {
+            // The top left end of the corridor
+            Self::Corridor => Some((0, 0)),
+            _ => None,
+        }
+    }
+
+    /// How the snake speeds up on these walls, or `None` for the mode's usual curve
+    pub fn speed_curve(&self) -> Option<SpeedCurve> {
+        match self {
+            // Every corner is tight enough already without the usual speed
+            Self::Corridor => Some(SpeedCurve::Exponential { factor: 0.97 }),
+            _ => None,
+        }
+    }
+
+    /// Makes the walls for a `size`x`size` board, always leaving the spawn and the cells to its
+    /// right clear for the snake to start in. Walls that would cut off part of the board are
+    /// thrown away for an empty one
+    pub fn generate(&self, size: u16, rng: &mut impl Rng) -> Vec<(u16, u16)> {
+        let walls = match self {
+            Self::Rings => rings(size, rng),
+            Self::Cross => cross(size, rng),
+            Self::Pillars => pillars(size, rng),
+            Self::Corridor => corridor(size),
+        };
+
+        let spawn = self.spawn().unwrap_or((size / 2, size / 2));
+        let walls: HashSet<_> = walls
+            .into_iter()
+            .filter(|&cell| !in_spawn_path(spawn, cell))
+            .collect();
+        if !is_connected(size, &walls) {
+            return Vec::new();
+        }
+
+        walls.into_iter().collect()
+    }
+}
+
+/// Cells the snake needs to get going from where it spawns
+const SPAWN_PATH_LENGTH: u16 = 4;
+
+fn in_spawn_path(spawn: (u16, u16), (x, y): (u16, u16)) -> bool {
+    y == spawn.1 && (spawn.0..=spawn.0 + SPAWN_PATH_LENGTH).contains(&x)
+}
+
+/// Whether every open cell of a `size`x`size` board can be reached from every other one
+fn is_connected(size: u16, walls: &HashSet<(u16, u16)>) -> bool {
+    let open_cells = size as usize * size as usize - walls.len();
+    let Some(start) = (0..size)
+        .flat_map(|x| (0..size).map(move |y| (x, y)))
+        .find(|cell| !walls.contains(cell))
+    else {
+        return true;
+    };
+
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbours = [
+            x.checked_sub(1).map(|x| (x, y)),
+            (x + 1 < size).then_some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            (y + 1 < size).then_some((x, y + 1)),
+        ];
+        for cell in neighbours.into_iter().flatten() {
+            if !walls.contains(&cell) && seen.insert(cell) {
+                queue.push_back(cell);
+            }
+        }
+    }
+
+    seen.len() == open_cells
+}
+
+fn rings(size: u16, rng: &mut impl Rng) -> Vec<(u16, u16)> {
+    const GAPS: usize = 2;
+
+    let mut walls = Vec::new();
+    let mut offset = 2;
+    // The innermost ring has to leave room for the snake in the middle
+    while offset + 3 < size / 2 {
+        let (min, max) = (offset, size - 1 - offset);
+        let ring: Vec<_> = (min..=max)
+            .flat_map(|i| [(i, min), (i, max), (min, i), (max, i)])
+            .collect();
+        // Gaps are two cells wide, and kept off the corners so they always open both ways
+        let gaps: Vec<_> = (0..GAPS)
+            .map(|_| {
+                let along = rng.random_range(min + 1..max - 1);
+                match rng.random_range(0..4) {
+                    0 => [(along, min), (along + 1, min)],
+                    1 => [(along, max), (along + 1, max)],
+                    2 => [(min, along), (min, along + 1)],
+                    _ => [(max, along), (max, along + 1)],
+                }
+            })
+            .collect();
+        walls.extend(
+            ring.into_iter()
+                .filter(|cell| !gaps.iter().flatten().any(|gap| gap == cell)),
+        );
+
+        offset += 3;
+    }
+    walls.sort_unstable();
+    walls.dedup();
+
+    walls
+}
+
+fn cross(size: u16, rng: &mut impl Rng) -> Vec<(u16, u16)> {
+    const GAP: u16 = 2;
+
+    let middle = size / 2;
+    // The arms stop short of the middle and of the border, so there's always a way around
+    let inner = 3;
+    let outer = middle.saturating_sub(2);
+    if outer <= inner + GAP {
+        return Vec::new();
+    }
+
+    let mut walls = Vec::new();
+    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let gap_start = rng.random_range(inner..=outer - GAP);
+        for distance in (inner..=outer).filter(|d| !(gap_start..gap_start + GAP).contains(d)) {
+            let x = middle as i32 + dx * distance as i32;
+            let y = middle as i32 + dy * distance as i32;
+            walls.push((x as u16, y as u16));
+        }
+    }
+
+    walls
+}
+
+fn pillars(size: u16, rng: &mut impl Rng) -> Vec<(u16, u16)> {
+    const SPACING: u16 = 3;
+    const CHANCE: f64 = 0.5;
+
+    // Every other cell between pillars is left open, so none of them can box anything in
+    let end = size.saturating_sub(2);
+    (2..end)
+        .step_by(SPACING as usize)
+        .flat_map(|x| (2..end).step_by(SPACING as usize).map(move |y| (x, y)))
+        .filter(|_| rng.random_bool(CHANCE))
+        .collect()
+}
+
+fn corridor(size: u16) -> Vec<(u16, u16)> {
+    const WIDTH: u16 = 2;
+
+    // Lanes two cells wide with a wall under each, the last wall opening at alternate ends so
+    // the corridor doubles back on itself. Rows left over at the bottom are filled in
+    let lanes = (size + 1) / (WIDTH + 1);
+    let mut walls = Vec::new();
+    for y in 0..size {
+        let lane = y / (WIDTH + 1);
+        if y >= lanes * (WIDTH + 1) - 1 {
+            walls.extend((0..size).map(|x| (x, y)));
+        } else if y % (WIDTH + 1) == WIDTH {
+            let opening = if lane.is_multiple_of(2) {
+                size - WIDTH..size
+            } else {
+                0..WIDTH
+            };
+            walls.extend((0..size).filter(|x| !opening.contains(x)).map(|x| (x, y)));
+        }
+    }
+
+    walls
+}
@@ -0,0 +1,206 @@
+use ratatui::symbols::border;
+
+use crate::{Direction, pickup::PickupKind};
+
+/// What everything on the board is drawn with, picked once at startup
+#[derive(Debug)]
+pub struct Glyphs {
+    pub body: &'static str,
+    /// The body while the snake can pass through itself
+    pub ghost_body: &'static str,
+    /// The body in Nokia mode, segmented like the old phone game's snake
+    pub nokia_body: &'static str,
+    /// The head pointing up, down, left, and right
+    heads: [&'static str; 4],
+    pub wall: &'static str,
+    /// Cells outside what's left of the arena
+    pub outside: &'static str,
+    pub rival: &'static str,
+    pub apple: &'static str,
+    pub rotten_apple: &'static str,
+    pub mine: &'static str,
+    pub portal: &'static str,
+    /// In the order of `PickupKind`, leaving out the decoy, which looks like an apple
+    pickups: [&'static str; 11],
+    /// Each pixel of the countdown's digits
+    pub countdown: &'static str,
+    /// Pointing up, down, left, and right at apples hidden in the fog
+    arrows: [&'static str; 4],
+    pub life: &'static str,
+    pub sprint: &'static str,
+    pub freeze: &'static str,
+    /// Between the hints under the board
+    pub separator: &'static str,
+    pub dash: &'static str,
+    pub solid_border: border::Set,
+    pub bounce_border: border::Set,
+    /// Hints that the snake can pass through it
+    pub wrap_border: border::Set,
+}
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        body: "██",
+        ghost_body: "░░",
+        nokia_body: "▆ ",
+        heads: ["▲▲", "▼▼", "◀◀", "▶▶"],
+        wall: "▓▓",
+        outside: "╱╱",
+        rival: "▒▒",
+        apple: "##",
+        rotten_apple: "oo",
+        mine: "XX",
+        portal: "@@",
+        pickups: [
+            "%%", "$$", ">>", "~~", "??", "<>", "!!", "&&", "**", "()", "::",
+        ],
+        countdown: "██",
+        arrows: ["↑", "↓", "←", "→"],
+        life: "♥",
+        sprint: "»»",
+        freeze: "❄",
+        separator: " · ",
+        dash: "—",
+        solid_border: border::THICK,
+        bounce_border: border::DOUBLE,
+        // Thick with gaps
+        wrap_border: border::Set {
+            horizontal_top: "┅",
+            horizontal_bottom: "┅",
+            vertical_left: "┇",
+            vertical_right: "┇",
+            ..border::THICK
+        },
+    };
+
+    /// For terminals that can't be trusted with anything else
+    pub const ASCII: Glyphs = Glyphs {
+        body: "[]",
+        ghost_body: "..",
+        nokia_body: "o ",
+        heads: ["/\\", "\\/", "<=", "=>"],
+        wall: "==",
+        outside: "//",
+        rival: "{}",
+        apple: "()",
+        rotten_apple: "oo",
+        mine: "XX",
+        portal: "@@",
+        // The magnet would look like an apple otherwise
+        pickups: [
+            "%%", "$$", ">>", "~~", "??", "<>", "!!", "&&", "**", "UU", "::",
+        ],
+        countdown: "##",
+        arrows: ["^", "v", "<", ">"],
+        life: "<3",
+        sprint: ">>",
+        freeze: "*",
+        separator: " - ",
+        dash: "-",
+        solid_border: ascii_border("+", "-", "|"),
+        bounce_border: ascii_border("#", "=", "H"),
+        wrap_border: ascii_border("+", ".", ":"),
+    };
+
+    pub fn head(&self, direction: Direction) -> &'static str {
+        self.heads[direction_index(direction)]
+    }
+
+    pub fn arrow(&self, direction: Direction) -> &'static str {
+        self.arrows[direction_index(direction)]
+    }
+
+    pub fn pickup(&self, kind: PickupKind) -> &'static str {
+        match kind {
+            // Only the color gives it away
+            PickupKind::Decoy => self.apple,
+            kind => self.pickups[kind as usize],
+        }
+    }
+}
+
+fn direction_index(direction: Direction) -> usize {
+    match direction {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+    }
+}
+
+const fn ascii_border(
+    corner: &'static str,
+    horizontal: &'static str,
+    vertical: &'static str,
+) -> border::Set {
+    border::Set {
+        top_left: corner,
+        top_right: corner,
+        bottom_left: corner,
+        bottom_right: corner,
+        vertical_left: vertical,
+        vertical_right: vertical,
+        horizontal_top: horizontal,
+        horizontal_bottom: horizontal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PICKUPS: [PickupKind; 11] = [
+        PickupKind::Poison,
+        PickupKind::Golden,
+        PickupKind::SpeedBoost,
+        PickupKind::SlowDown,
+        PickupKind::Ghost,
+        PickupKind::Shrink,
+        PickupKind::Curse,
+        PickupKind::Reverse,
+        PickupKind::Bonus,
+        PickupKind::Magnet,
+        PickupKind::Freeze,
+    ];
+
+    /// Everything that can sit in a cell of the board, apart from the snake
+    fn cell_glyphs(glyphs: &Glyphs) -> Vec<&'static str> {
+        let mut cells = vec![
+            glyphs.wall,
+            glyphs.outside,
+            glyphs.rival,
+            glyphs.apple,
+            glyphs.rotten_apple,
+            glyphs.mine,
+            glyphs.portal,
+        ];
+        cells.extend(PICKUPS.map(|kind| glyphs.pickup(kind)));
+        cells
+    }
+
+    #[test]
+    fn nothing_on_the_board_looks_alike() {
+        for glyphs in [&Glyphs::UNICODE, &Glyphs::ASCII] {
+            let mut cells = cell_glyphs(glyphs);
+            cells.extend([glyphs.body, glyphs.ghost_body, glyphs.nokia_body]);
+            cells.extend(Direction::ALL.map(|direction| glyphs.head(direction)));
+            for (index, cell) in cells.iter().enumerate() {
+                assert!(!cells[index + 1..].contains(cell), "{cell}");
+            }
+        }
+    }
+
+    #[test]
+    fn the_countdown_stands_out_from_the_board() {
+        for glyphs in [&Glyphs::UNICODE, &Glyphs::ASCII] {
+            assert!(!cell_glyphs(glyphs).contains(&glyphs.countdown));
+        }
+    }
+
+    #[test]
+    fn decoys_look_like_apples() {
+        assert_eq!(Glyphs::UNICODE.pickup(PickupKind::Decoy), "##");
+        assert_eq!(Glyphs::ASCII.pickup(PickupKind::Decoy), "()");
+        assert_eq!(Glyphs::ASCII.pickup(PickupKind::Magnet), "UU");
+    }
+}
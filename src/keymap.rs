@@ -0,0 +1,360 @@
+use anyhow::{Context, Result, bail};
+use crossterm::event::KeyCode;
+
+use crate::{Direction, glyphs::Glyphs, storage};
+
+/// Read at startup, with lines like `up = Up w k` replacing the keys for an action
+const FILE_NAME: &str = "keys.txt";
+
+/// Keys that do something of their own, so they can't be given to an action
+const RESERVED: [KeyCode; 8] = [
+    KeyCode::Char('t'),
+    KeyCode::Char('g'),
+    KeyCode::Char('u'),
+    KeyCode::Char('m'),
+    KeyCode::Char('?'),
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+];
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Sprint,
+    Brake,
+    Pause,
+    Restart,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Sprint,
+        Self::Brake,
+        Self::Pause,
+        Self::Restart,
+        Self::Quit,
+    ];
+
+    /// How the action is written in the keys file
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Sprint => "sprint",
+            Self::Brake => "brake",
+            Self::Pause => "pause",
+            Self::Restart => "restart",
+            Self::Quit => "quit",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn direction(&self) -> Option<Direction> {
+        match self {
+            Self::Up => Some(Direction::Up),
+            Self::Down => Some(Direction::Down),
+            Self::Left => Some(Direction::Left),
+            Self::Right => Some(Direction::Right),
+            Self::Sprint | Self::Brake | Self::Pause | Self::Restart | Self::Quit => None,
+        }
+    }
+}
+
+/// Which keys do what
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let keys = |special: KeyCode, chars: &'static str| {
+            [special]
+                .into_iter()
+                .chain(chars.chars().map(KeyCode::Char))
+                .collect()
+        };
+        Self {
+            bindings: vec![
+                (Action::Up, keys(KeyCode::Up, "wk")),
+                (Action::Down, keys(KeyCode::Down, "sj")),
+                (Action::Left, keys(KeyCode::Left, "ah")),
+                (Action::Right, keys(KeyCode::Right, "dl")),
+                (Action::Sprint, vec![KeyCode::Char(' ')]),
+                (Action::Brake, vec![KeyCode::Char('b')]),
+                (Action::Pause, vec![KeyCode::Char('p'), KeyCode::Esc]),
+                (Action::Restart, vec![KeyCode::Char('r')]),
+                (Action::Quit, vec![KeyCode::Char('q')]),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// Reads the keys file over the default keys, failing on anything it can't make sense of or
+    /// any key bound twice
+    pub fn load() -> Result<Self> {
+        Self::parse(&storage::read(FILE_NAME)).with_context(|| format!("Invalid {FILE_NAME}"))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, keys) = line
+                .split_once('=')
+                .with_context(|| format!("Expected action = keys on line {}", number + 1))?;
+            let action = Action::from_name(name.trim()).with_context(|| {
+                let names: Vec<_> = Action::ALL.iter().map(Action::name).collect();
+                format!(
+                    "Unknown action {} on line {}, expected one of {}",
+                    name.trim(),
+                    number + 1,
+                    names.join(", ")
+                )
+            })?;
+            let keys = keys
+                .split_whitespace()
+                .map(|key| {
+                    key_from_name(key)
+                        .with_context(|| format!("Unknown key {key} on line {}", number + 1))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if keys.is_empty() {
+                bail!("No keys for {} on line {}", action.name(), number + 1);
+            }
+
+            for (bound, bound_keys) in &mut keymap.bindings {
+                if *bound == action {
+                    *bound_keys = keys.clone();
+                }
+            }
+        }
+
+        keymap.check()?;
+        Ok(keymap)
+    }
+
+    /// Fails if a key is bound to two actions, or to one and something else as well
+    fn check(&self) -> Result<()> {
+        for (index, (action, keys)) in self.bindings.iter().enumerate() {
+            for key in keys {
+                if RESERVED.contains(key) {
+                    bail!(
+                        "{} can't be used for {}, it's taken",
+                        key_name(*key),
+                        action.name()
+                    );
+                }
+                if let Some((other, _)) = self.bindings[index + 1..]
+                    .iter()
+                    .find(|(_, other_keys)| other_keys.contains(key))
+                {
+                    bail!(
+                        "{} is bound to both {} and {}",
+                        key_name(*key),
+                        action.name(),
+                        other.name()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// What the key is bound to, if anything
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&code))
+            .map(|(action, _)| *action)
+    }
+
+    fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys)
+    }
+
+    /// The first key bound to `action`, as it's shown to the player
+    pub fn key_name(&self, action: Action, glyphs: &Glyphs) -> String {
+        self.keys(action)
+            .first()
+            .map_or_else(String::new, |&key| shown_name(key, glyphs))
+    }
+
+    /// Every key bound to `action`, as they're shown to the player
+    pub fn key_names(&self, action: Action, glyphs: &Glyphs) -> String {
+        let names: Vec<_> = self
+            .keys(action)
+            .iter()
+            .map(|&key| shown_name(key, glyphs))
+            .collect();
+        names.join("/")
+    }
+
+    /// The direction the key steers in, if it's bound to one
+    pub fn direction(&self, code: KeyCode) -> Option<Direction> {
+        self.action(code).and_then(|action| action.direction())
+    }
+}
+
+/// Reads a key as written in the keys file, either a single character or a name like `Up`
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    Some(match name.to_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "space" => KeyCode::Char(' '),
+        _ => return None,
+    })
+}
+
+fn shown_name(code: KeyCode, glyphs: &Glyphs) -> String {
+    match code {
+        KeyCode::Up => glyphs.arrow(Direction::Up).to_string(),
+        KeyCode::Down => glyphs.arrow(Direction::Down).to_string(),
+        KeyCode::Left => glyphs.arrow(Direction::Left).to_string(),
+        KeyCode::Right => glyphs.arrow(Direction::Right).to_string(),
+        code => key_name(code),
+    }
+}
+
+/// Writes a key the way the keys file does
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        code => format!("{code:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_keys_steer_the_right_way() {
+        let keymap = Keymap::default();
+        let expected = [
+            (
+                Direction::Up,
+                [KeyCode::Up, KeyCode::Char('w'), KeyCode::Char('k')],
+            ),
+            (
+                Direction::Down,
+                [KeyCode::Down, KeyCode::Char('s'), KeyCode::Char('j')],
+            ),
+            (
+                Direction::Left,
+                [KeyCode::Left, KeyCode::Char('a'), KeyCode::Char('h')],
+            ),
+            (
+                Direction::Right,
+                [KeyCode::Right, KeyCode::Char('d'), KeyCode::Char('l')],
+            ),
+        ];
+        for (direction, keys) in expected {
+            for key in keys {
+                assert_eq!(keymap.direction(key), Some(direction), "{key:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn other_keys_dont_steer() {
+        let keymap = Keymap::default();
+        for key in [
+            KeyCode::Char('x'),
+            KeyCode::Char('p'),
+            KeyCode::Char('q'),
+            KeyCode::Char(' '),
+            KeyCode::Enter,
+            KeyCode::F(1),
+        ] {
+            assert_eq!(keymap.direction(key), None, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn keys_file_replaces_an_actions_keys() {
+        let keymap = Keymap::parse("# comment\n\nup = i Up\nleft = Left\n").unwrap();
+        assert_eq!(keymap.direction(KeyCode::Char('i')), Some(Direction::Up));
+        assert_eq!(keymap.direction(KeyCode::Up), Some(Direction::Up));
+        assert_eq!(keymap.direction(KeyCode::Char('w')), None);
+        assert_eq!(keymap.direction(KeyCode::Char('a')), None);
+        assert_eq!(keymap.action(KeyCode::Char('q')), Some(Action::Quit));
+    }
+
+    #[test]
+    fn keys_bound_twice_are_rejected() {
+        let error = Keymap::parse("quit = w").unwrap_err();
+        assert_eq!(error.to_string(), "w is bound to both up and quit");
+        assert!(Keymap::parse("up = Up\ndown = Up").is_err());
+        let error = Keymap::parse("pause = Space").unwrap_err();
+        assert_eq!(error.to_string(), "Space is bound to both sprint and pause");
+    }
+
+    #[test]
+    fn reserved_keys_are_rejected() {
+        let error = Keymap::parse("pause = Enter").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Enter can't be used for pause, it's taken"
+        );
+        assert!(Keymap::parse("restart = Tab").is_err());
+        assert!(Keymap::parse("up = g").is_err());
+    }
+
+    #[test]
+    fn arrow_keys_are_shown_with_the_glyphs() {
+        let keymap = Keymap::parse("up = Up w\npause = Esc").unwrap();
+        assert_eq!(keymap.key_name(Action::Up, &Glyphs::UNICODE), "↑");
+        assert_eq!(keymap.key_name(Action::Up, &Glyphs::ASCII), "^");
+        assert_eq!(keymap.key_name(Action::Pause, &Glyphs::ASCII), "Esc");
+        assert_eq!(keymap.key_name(Action::Quit, &Glyphs::ASCII), "q");
+        assert_eq!(keymap.key_names(Action::Up, &Glyphs::ASCII), "^/w");
+        assert_eq!(keymap.key_names(Action::Down, &Glyphs::ASCII), "v/s/j");
+        // Errors name keys as they're written in the file
+        let error = Keymap::parse("quit = Up").unwrap_err();
+        assert_eq!(error.to_string(), "Up is bound to both up and quit");
+    }
+
+    #[test]
+    fn unreadable_lines_are_rejected() {
+        assert!(Keymap::parse("up").is_err());
+        assert!(Keymap::parse("jump = x").is_err());
+        assert!(Keymap::parse("up = F5").is_err());
+        assert!(Keymap::parse("up =").is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::VecDeque;
+
+use crossterm::event::KeyCode;
+
+const SEQUENCE: [KeyCode; 10] = [
+    KeyCode::Up,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Char('b'),
+    KeyCode::Char('a'),
+];
+
+/// Watches the keys pressed for Up Up Down Down Left Right Left Right B A
+#[derive(Debug, Default)]
+pub struct KonamiCode {
+    recent: VecDeque<KeyCode>,
+}
+
+impl KonamiCode {
+    /// Records a key press, returning whether it finished the code
+    pub fn press(&mut self, code: KeyCode) -> bool {
+        if self.recent.len() == SEQUENCE.len() {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(code);
+
+        self.recent.iter().eq(SEQUENCE.iter())
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use crate::patrol::{Axis, Patroller};
+
+/// Hand-placed obstacle arrangements, scaled to the size of the board
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    #[default]
+    Empty,
+    /// A ring of walls with a gap in the middle of each side
+    Box,
+    /// Two horizontal bars across the top and bottom of the board
+    Bars,
+    /// A sparse grid of single wall cells
+    Pillars,
+    /// No walls, but a pair of portals in opposite corners
+    Portals,
+    /// No walls, but a few obstacles sliding back and forth
+    Patrol,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 6] = [
+        Self::Empty,
+        Self::Box,
+        Self::Bars,
+        Self::Pillars,
+        Self::Portals,
+        Self::Patrol,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Box => "box",
+            Self::Bars => "bars",
+            Self::Pillars => "pillars",
+            Self::Portals => "portals",
+            Self::Patrol => "patrol",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|layout| layout.name() == name)
+    }
+
+    /// Returns the blocked cells of this layout on a `width`x`height` board
+    pub fn obstacles(&self, width: u16, height: u16) -> HashSet<(u16, u16)> {
+        let mut obstacles = HashSet::new();
+        // None of the layouts fit on a board this small
+        if width < 10 || height < 10 {
+            return obstacles;
+        }
+
+        match self {
+            Self::Empty | Self::Portals | Self::Patrol => {}
+            Self::Box => {
+                let (max_x, max_y) = (width - 4, height - 4);
+                // Leave a two cell gap in the middle of each side
+                for x in (3..=max_x).filter(|&x| x != width / 2 - 1 && x != width / 2) {
+                    obstacles.extend([(x, 3), (x, max_y)]);
+                }
+                for y in (3..=max_y).filter(|&y| y != height / 2 - 1 && y != height / 2) {
+                    obstacles.extend([(3, y), (max_x, y)]);
+                }
+            }
+            Self::Bars => {
+                for x in 4..width - 4 {
+                    obstacles.extend([(x, 4), (x, height - 5)]);
+                }
+            }
+            Self::Pillars => {
+                for x in (4..width - 3).step_by(5) {
+                    for y in (4..height - 3).step_by(5) {
+                        obstacles.insert((x, y));
+                    }
+                }
+            }
+        }
+
+        obstacles
+    }
+
+    /// Returns the linked portal cells of this layout on a `width`x`height` board
+    pub fn portals(&self, width: u16, height: u16) -> Option<((u16, u16), (u16, u16))> {
+        match self {
+            Self::Portals if width >= 8 && height >= 8 => Some(((3, 3), (width - 4, height - 4))),
+            _ => None,
+        }
+    }
+
+    /// Returns the moving obstacles of this layout on a `width`x`height` board, where they start
+    pub fn patrollers(&self, width: u16, height: u16) -> Vec<Patroller> {
+        match self {
+            // Two sweep across the top and bottom quarters in opposite directions, and one runs
+            // down the left side, all clear of the spawn points
+            Self::Patrol if width >= 10 && height >= 10 => vec![
+                Patroller::new((2, height / 4), Axis::Horizontal),
+                Patroller {
+                    forward: false,
+                    ..Patroller::new((width - 3, height - 1 - height / 4), Axis::Horizontal)
+                },
+                Patroller::new((1, 2), Axis::Vertical),
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Returns the extra walls for `level`, each level adds another pair of mirrored bars working
+/// inwards from the border and alternating between horizontal and vertical
+pub fn level_obstacles(level: u32, width: u16, height: u16) -> HashSet<(u16, u16)> {
+    let mut obstacles = HashSet::new();
+
+    for bar in 0..level.saturating_sub(1) {
+        let offset = 2 + 3 * (bar / 2) as u16;
+        let horizontal = bar % 2 == 0;
+        let (length, across) = if horizontal {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        // Bars stop short of each other so every ring keeps its corners open
+        let span = offset + 2..length.saturating_sub(offset + 2);
+        if span.is_empty() || offset * 2 + 1 >= across {
+            break;
+        }
+
+        for i in span {
+            if horizontal {
+                obstacles.extend([(i, offset), (i, height - 1 - offset)]);
+            } else {
+                obstacles.extend([(offset, i), (width - 1 - offset, i)]);
+            }
+        }
+    }
+
+    obstacles
+}
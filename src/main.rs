@@ -1,249 +1,4535 @@
-use std::time::{Duration, Instant};
+mod achievements;
+mod ai;
+mod apple;
+mod best;
+mod daily;
+mod debug;
+mod generator;
+mod glyphs;
+mod keymap;
+mod konami;
+mod layout;
+mod maze;
+mod palette;
+mod patrol;
+mod pickup;
+mod popup;
+mod rewind;
+mod rival;
+mod seed;
+mod settings;
+mod speed;
+mod splits;
+mod storage;
+mod tutorial;
 
+use std::{
+    cmp::Ordering,
+    collections::{HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use achievements::{Achievement, Achievements};
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
-use rand::seq::IndexedRandom;
+use apple::{Apple, AppleColor, AppleState, MegaApple};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+    MouseEvent, MouseEventKind,
+};
+use daily::Date;
+use debug::DebugTools;
+use glyphs::Glyphs;
+use keymap::{Action, Keymap};
+use konami::KonamiCode;
+use patrol::Patroller;
+use pickup::{Pickup, PickupKind};
+use popup::Popup;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
 use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
     layout::Rect,
-    symbols::border,
-    text::{Line, Text},
-    widgets::{Block, Widget},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Clear, Widget},
 };
+use rewind::{Effects, History, Snapshot};
+use rival::{Controller, Outcome, Rival};
+use settings::{BorderMode, Difficulty, Mirror, Mode, Settings, SettingsRow};
+use speed::SpeedCurve;
+use tutorial::{Step, Tutorial};
 
 fn main() -> Result<()> {
+    let mut settings = Settings::from_args(std::env::args().skip(1))?;
+    settings.keymap = Keymap::load()?;
+    let mouse = settings.mouse;
     let terminal = ratatui::init();
-    let result = Game::default().run(terminal);
+    if mouse {
+        crossterm::execute!(std::io::stdout(), EnableMouseCapture)?;
+    }
+    let result = Game::new(settings).run(terminal);
+    if mouse {
+        crossterm::execute!(std::io::stdout(), DisableMouseCapture).ok();
+    }
     ratatui::restore();
     result
 }
 
 #[derive(Debug)]
 pub struct Game {
+    width: u16,
+    height: u16,
     last_moved: Instant,
     snake: Vec<(u16, u16)>,
     snake_direction: Direction,
-    apple_position: (u16, u16),
+    apples: Vec<Apple>,
+    /// How many pairs of apples have spawned together, to tell them apart
+    apple_pairs: u32,
+    /// How many times apples have been put down to keep the board stocked, every few of which is
+    /// a choice between two
+    apple_spawns: u32,
+    /// Which apple in `AppleColor::ORDER` has to be eaten next in sequence mode
+    next_color: usize,
+    mega_apple: Option<MegaApple>,
+    /// Move ticks until an evasive apple can hop again
+    apple_hop_cooldown: u32,
+    /// Move ticks until the next earthquake moves the apples
+    earthquake_ticks: u32,
     snake_move_time: u64,
     game_over: bool,
+    obstacles: HashSet<(u16, u16)>,
+    /// Obstacles that slide back and forth, which are walls for everything else
+    patrollers: Vec<Patroller>,
+    /// Two linked cells, entering one moves the head onto the other
+    portals: Option<((u16, u16), (u16, u16))>,
+    pickups: Vec<Pickup>,
+    score: u32,
+    apples_eaten: u32,
+    /// Chance per move tick that each kind of pickup appears while there is none of it
+    pickup_chances: Vec<(PickupKind, f64)>,
+    /// Move ticks left on the speed boost
+    boost_ticks: u32,
+    /// Move ticks left on the slow down
+    slow_ticks: u32,
+    /// Move ticks left on the time freeze, while it lasts the snake moves at a fixed speed
+    freeze_ticks: u32,
+    /// Move ticks left on the magnet, while it lasts the apples drift towards the head
+    magnet_ticks: u32,
+    /// Move ticks left on the ghost effect, while it lasts the snake passes through itself
+    ghost_ticks: u32,
+    /// Whether the one grace tick after the ghost effect wears off has been handed out
+    ghost_grace_used: bool,
+    /// Move ticks left with the controls reversed
+    reversed_ticks: u32,
+    ticks_since_apple: u32,
+    /// Move ticks left showing the score in a warning color after it decays
+    decay_flash_ticks: u32,
+    /// Apples eaten in quick succession, each one is worth this many points
+    combo: u32,
+    best_combo: u32,
+    level: u32,
+    /// Message shown over the board when a level starts or a life is lost, and when it was
+    /// shown. The snake waits until it's gone
+    banner: Option<(String, Instant)>,
+    lives: u32,
+    /// Messages waiting to be shown over the board without stopping the game, the first one is
+    /// showing until the tick in `message_expires`
+    messages: VecDeque<String>,
+    message_expires: u32,
+    /// Second snake, only present when playing against someone
+    rival: Option<Rival>,
+    /// Who won a finished game between two players
+    outcome: Option<Outcome>,
+    /// Corners of the part of the board that's still safe, everything outside it is deadly
+    min_bound: (u16, u16),
+    max_bound: (u16, u16),
+    /// Cells that end the game on contact, added as it goes on
+    mines: HashSet<(u16, u16)>,
+    /// Move ticks played so far
+    ticks: u32,
+    /// Walls scattered at random when the game started
+    random_walls: HashSet<(u16, u16)>,
+    /// Walls from the generator, made once per game so they stay put between levels
+    generated_walls: HashSet<(u16, u16)>,
+    /// Every random choice about the board comes from here
+    rng: StdRng,
+    /// The day of the daily challenge being played, if it is one
+    daily: Option<Date>,
+    /// Best daily challenge score from earlier attempts today
+    daily_best: Option<u32>,
+    /// The best score ever saved for these rules, from before this game
+    best_score: u32,
+    /// Whether the snake is moving at double speed until the next apple
+    sprinting: bool,
+    /// Move ticks left at brake speed
+    brake_ticks: u32,
+    /// Move ticks until the brake can be used again
+    brake_cooldown: u32,
+    /// Whether the snake is being held for a tick before a crash, waiting for a turn
+    grace_tick: bool,
+    /// Passes left through the border
+    phases: u32,
+    /// Move ticks left flashing the border after a pass through it
+    phase_flash_ticks: u32,
+    /// Points just scored, floating up over the board
+    popups: Vec<Popup>,
+    /// Move ticks left flashing the score and tail after eating an apple
+    eat_flash_ticks: u32,
+    konami_code: KonamiCode,
+    /// Recent ticks that can be rewound in practice mode
+    history: History,
+    /// The snake is drawn in every color for the rest of the run
+    rainbow: bool,
+    /// Segments still to be added to the snake, one per move
+    pending_growth: usize,
+    achievements: Achievements,
+    /// The last achievement unlocked and when, shown over the board for a moment
+    achievement_banner: Option<(Achievement, Instant)>,
+    /// Whether the list of achievements is shown over the board
+    showing_achievements: bool,
+    /// When the list of controls was opened, while it's shown over the board
+    help_opened: Option<Instant>,
+    /// When the game was paused, if it is
+    paused_at: Option<Instant>,
+    /// When the countdown before the snake moves started, while it's counting down
+    countdown: Option<Instant>,
+    /// When the snake crashed, while the animation of it dying plays before the game over screen
+    dying: Option<Instant>,
+    /// Whether the terminal is too small to show the board, which holds the game up
+    too_small: bool,
+    /// Whether the terminal should be drawn in color
+    colors: bool,
+    /// Whether the terminal can show any RGB color, rather than only its 256 color palette
+    truecolor: bool,
+    /// What the board is drawn with, plain ASCII if the terminal can't be trusted with more
+    glyphs: &'static Glyphs,
+    /// Whether the pre-game screen is waiting for a mode to be picked
+    choosing_mode: bool,
+    /// Index into `Mode::ALL` of the mode highlighted on the pre-game screen
+    menu_selection: usize,
+    /// Index into `SettingsRow::ALL` of the row highlighted on the settings screen, while it's
+    /// showing
+    settings_row: Option<usize>,
+    /// When the pre-game screen was last touched, to start the demo once it's been left alone
+    idle_since: Instant,
+    /// Whether the game is playing itself behind the pre-game screen
+    demo: bool,
+    /// The seed code typed so far on the pre-game screen, while one is being entered
+    entering_seed: Option<String>,
+    /// Whether the speed screen is showing before a Nokia game
+    choosing_level: bool,
+    tutorial: Option<Tutorial>,
+    /// Cheat keys and their state, in a `--debug` game
+    debug: Option<DebugTools>,
+    /// Total duration of the ticks played so far, which only advances while the snake moves
+    play_time: Duration,
+    /// Whether a timed game ended because the clock ran out
+    time_up: bool,
+    /// Whether the game ended because the snake filled the board
+    won: bool,
+    /// Play time when the score reached each multiple of `splits::SPLIT_SCORE`
+    splits: Vec<Duration>,
+    /// The fastest saved times for each split, from before this game
+    best_splits: Vec<Duration>,
+    settings: Settings,
 }
 
 impl Default for Game {
     fn default() -> Self {
-        Self {
+        Self::new(Settings::default())
+    }
+}
+
+impl Game {
+    const APPLES_PER_GOLDEN: u32 = 5;
+    const APPLES_PER_BONUS: u32 = 10;
+    const APPLES_PER_MINE: u32 = 15;
+    const TICKS_PER_MINE: u32 = 100;
+    const MAX_MINES: usize = 15;
+    const TICKS_PER_CONTRACTION: u32 = 20;
+    /// Move ticks between each step the patrollers take
+    const PATROL_TICKS: u32 = 3;
+    /// The shrinking arena stops closing in once it's this small
+    const MIN_ARENA_SIZE: u16 = 6;
+    const GROWING_START_SIZE: u16 = 10;
+    const GROWING_MAX_SIZE: u16 = 24;
+    /// Apples between each ring a growing board gains
+    const APPLES_PER_GROWTH: u32 = 8;
+    const APPLE_RELOCATE_TICKS: u32 = 60;
+    const APPLE_BLINK_TICKS: u32 = 10;
+    /// Move ticks an apple can go uneaten before it rots
+    const APPLE_ROT_TICKS: u32 = 100;
+    /// Move ticks a rotten apple stays before a fresh one replaces it somewhere else
+    const ROTTEN_APPLE_TICKS: u32 = 50;
+    const ROTTEN_APPLE_PENALTY: u32 = 2;
+    const DECOY_PENALTY: u32 = 3;
+    const MEGA_APPLE_SIZE: u16 = 2;
+    const MEGA_APPLE_SCORE: u32 = 4;
+    /// Every this many apples spawned is a choice between a normal apple and a premium one
+    const SPAWNS_PER_CHOICE: u32 = 5;
+    /// How many normal apples a premium apple is worth
+    const PREMIUM_APPLE_VALUE: u32 = 3;
+    const MEGA_APPLE_GROWTH: usize = 2;
+    const MEGA_APPLE_TICKS: u32 = 80;
+    /// Move ticks without an apple for each point lost to score decay
+    const SCORE_DECAY_TICKS: u32 = 30;
+    const DECAY_FLASH_TICKS: u32 = 3;
+    /// How close the head can get to an evasive apple before it hops away
+    const EVADE_DISTANCE: u16 = 3;
+    const APPLE_HOP_COOLDOWN: u32 = 5;
+    /// Move ticks between earthquakes, picked at random each time
+    const EARTHQUAKE_TICKS: std::ops::RangeInclusive<u32> = 200..=400;
+    const EARTHQUAKE_WARNING_TICKS: u32 = 3;
+    /// Chance every tick of a mega apple appearing when there isn't one
+    const MEGA_APPLE_CHANCE: f64 = 0.01;
+    const BOOST_TICKS: u32 = 30;
+    /// How much shorter the move interval is while boosted
+    const BOOST_PERCENT: u64 = 40;
+    const SLOW_TICKS: u32 = 40;
+    /// How much longer the move interval is while slowed down
+    const SLOW_MS: u64 = 50;
+    const BRAKE_TICKS: u32 = 10;
+    /// Move ticks from using the brake until it can be used again
+    const BRAKE_COOLDOWN_TICKS: u32 = 50;
+    /// Extra score for an apple eaten while sprinting
+    const SPRINT_BONUS: u32 = 1;
+    /// How close to the fastest speed the snake has to be before slow downs appear
+    const SLOW_SPAWN_MARGIN: u64 = 20;
+    /// The usual top speed, which some rules still measure against
+    const MIN_MOVE_TIME: u64 = Difficulty::Normal.min_move_time();
+    /// Zen mode never speeds up past this
+    const ZEN_MOVE_TIME: u64 = 150;
+    const UNBOUNDED_MIN_MOVE_TIME: u64 = 20;
+    /// How much faster the snake gets with every apple once it's past the usual top speed
+    const UNBOUNDED_SPEED_STEP: u64 = 2;
+    const HARDCORE_START_MOVE_TIME: u64 = 120;
+    const HARDCORE_MIN_MOVE_TIME: u64 = 35;
+    /// How much faster the snake gets with every apple in hardcore mode
+    const HARDCORE_SPEED_STEP: u64 = 15;
+    const GHOST_TICKS: u32 = 20;
+    const MAGNET_TICKS: u32 = 20;
+    const FREEZE_TICKS: u32 = 15;
+    /// The move time while time is frozen, whatever the speed was
+    const FREEZE_MOVE_TIME: u64 = 200;
+    /// Move ticks between each step the apples take towards a magnet
+    const MAGNET_DRIFT_TICKS: u32 = 3;
+    const REVERSED_TICKS: u32 = 15;
+    const COMBO_WINDOW_MS: u64 = 3000;
+    const TIME_ATTACK_DURATION: Duration = Duration::from_secs(120);
+    const FRENZY_DURATION: Duration = Duration::from_secs(60);
+    const FRENZY_APPLES: usize = 8;
+    const FRENZY_MOVE_TIME: u64 = 120;
+    const SHRINK_SEGMENTS: usize = 3;
+    /// Shrinking never takes the snake below this length
+    const SHRINK_MIN_LENGTH: usize = 3;
+    /// The snake has to be longer than this before shrink pickups appear
+    const SHRINK_SPAWN_LENGTH: usize = 15;
+    const START_MOVE_TIME: u64 = Difficulty::Normal.start_move_time();
+    const APPLES_PER_LEVEL: u32 = 10;
+    /// How much of the speed gained during a level is given back when the next one starts
+    const LEVEL_SLOWDOWN: u64 = 40;
+    const BANNER_TIME: Duration = Duration::from_secs(1);
+    /// Seconds counted down before the snake starts moving
+    const COUNTDOWN_SECONDS: u64 = 3;
+    const MESSAGE_TIME: Duration = Duration::from_secs(1);
+    /// How player two steers in versus mode, which the keys file leaves alone
+    const SECOND_PLAYER_KEYS: [(char, Direction); 4] = [
+        ('w', Direction::Up),
+        ('s', Direction::Down),
+        ('a', Direction::Left),
+        ('d', Direction::Right),
+    ];
+    /// How far from the head the board can be seen in fog of war
+    const FOG_RADIUS: u16 = 6;
+    /// How long the snake is when it comes back after losing a life
+    const RESPAWN_LENGTH: usize = 3;
+    /// Every this many segments of a snake that loses a life is left behind as an apple
+    const SEGMENTS_PER_DROPPED_APPLE: usize = 5;
+    /// How many apples' worth of speed the snake loses when it comes back
+    const RESPAWN_SLOWDOWN_STEPS: u64 = 2;
+    const ACHIEVEMENT_BANNER_TIME: Duration = Duration::from_secs(2);
+    const RIVAL_RESPAWN_TICKS: u32 = 25;
+    /// Free cells needed in front of a spawn point
+    const SPAWN_RUNWAY: u16 = 3;
+    const SECRET_CODE_BONUS: u32 = 10;
+    /// How long the pre-game screen waits for a key before the demo starts
+    const DEMO_IDLE_TIME: Duration = Duration::from_secs(10);
+    /// How far apart the apples in sequence mode spawn from each other
+    const SEQUENCE_APPLE_SPACING: u16 = 5;
+    const SEQUENCE_PENALTY: u32 = 1;
+    /// Chance of a second apple spawning alongside a new one
+    const DOUBLE_APPLE_CHANCE: f64 = 0.1;
+    const PHASE_CHARGES: u32 = 2;
+    /// Apples between each refill of the phase charges
+    const APPLES_PER_PHASE_REFILL: u32 = 15;
+    const PHASE_FLASH_TICKS: u32 = 3;
+    const EAT_FLASH_TICKS: u32 = 2;
+    /// Faint enough that nothing drawn over it is any harder to see
+    const GRID_COLOR: Color = Color::Indexed(235);
+    /// How long the snake dies for, flashing at first and then vanishing from the tail
+    const DEATH_TIME: Duration = Duration::from_secs(1);
+    const DEATH_FLASH_TIME: Duration = Duration::from_millis(400);
+    /// Move times for each of Nokia mode's speed levels, slowest first
+    const NOKIA_MOVE_TIMES: [u64; Settings::MAX_SPEED_LEVEL as usize] =
+        [400, 340, 290, 240, 200, 165, 135, 105, 80];
+    const NOKIA_POINTS_PER_LEVEL: u32 = 9;
+    const RAINBOW: [Color; 6] = [
+        Color::Red,
+        Color::Yellow,
+        Color::Green,
+        Color::Cyan,
+        Color::Blue,
+        Color::Magenta,
+    ];
+
+    pub fn new(settings: Settings) -> Self {
+        let mode = settings.mode.unwrap_or_default();
+        // A seed someone has shared takes over from the daily board
+        let daily = (settings.daily && settings.seed.is_none()).then(Date::today);
+        let (board_width, board_height) = if settings.growing {
+            (Self::GROWING_MAX_SIZE, Self::GROWING_MAX_SIZE)
+        } else if settings.fit_terminal
+            && let Some(size) = terminal_board_size()
+        {
+            size
+        } else {
+            (settings.board_size(), settings.board_size())
+        };
+        // A growing board starts out as a small arena in the middle of the full size one
+        let (margin, arena_width, arena_height) = if settings.growing {
+            let margin = (board_width - Self::GROWING_START_SIZE) / 2;
+            (margin, Self::GROWING_START_SIZE, Self::GROWING_START_SIZE)
+        } else {
+            (0, board_width, board_height)
+        };
+        let (width, height, spawn) = match &settings.maze {
+            Some(maze) => (maze.width, maze.height, maze.spawn),
+            // Versus games start the snakes in opposite halves of the board
+            None if settings.opponent.is_some() => (
+                board_width,
+                board_height,
+                (margin + arena_width / 4, margin + arena_height / 2),
+            ),
+            None => (
+                board_width,
+                board_height,
+                (margin + arena_width / 2, margin + arena_height / 2),
+            ),
+        };
+        // Some generated walls have their own place to start, moved along so the starting body
+        // fits behind it
+        let spawn = match settings.generator.and_then(|generator| generator.spawn()) {
+            Some((x, y)) if settings.maze.is_none() && !settings.growing => {
+                let behind = (settings.starting_length as u16).saturating_sub(1);
+                ((x + behind).min(width - 1 - Self::SPAWN_RUNWAY), y)
+            }
+            _ => spawn,
+        };
+
+        let mut game = Self {
+            width,
+            height,
             last_moved: Instant::now(),
-            snake: vec![(Self::BOARD_SIZE / 2, Self::BOARD_SIZE / 2)],
+            snake: vec![spawn],
             snake_direction: Direction::Right,
-            apple_position: (Self::BOARD_SIZE / 2, Self::BOARD_SIZE / 3),
-            snake_move_time: 200,
+            apples: Vec::new(),
+            apple_pairs: 0,
+            apple_spawns: 0,
+            next_color: 0,
+            mega_apple: None,
+            apple_hop_cooldown: 0,
+            earthquake_ticks: 0,
+            snake_move_time: Self::start_move_time(&settings),
             game_over: false,
+            obstacles: HashSet::new(),
+            patrollers: settings.layout.patrollers(width, height),
+            portals: settings.layout.portals(width, height),
+            pickups: Vec::new(),
+            score: 0,
+            apples_eaten: 0,
+            pickup_chances: vec![
+                (PickupKind::Poison, 0.02),
+                (PickupKind::SpeedBoost, 0.01),
+                (PickupKind::SlowDown, 0.005),
+                (PickupKind::Ghost, 0.004),
+                (PickupKind::Shrink, 0.004),
+                (PickupKind::Curse, 0.006),
+                (PickupKind::Reverse, 0.002),
+                (PickupKind::Magnet, 0.004),
+                (PickupKind::Freeze, 0.002),
+                (PickupKind::Decoy, 0.005),
+            ],
+            boost_ticks: 0,
+            slow_ticks: 0,
+            magnet_ticks: 0,
+            freeze_ticks: 0,
+            ghost_ticks: 0,
+            ghost_grace_used: false,
+            reversed_ticks: 0,
+            ticks_since_apple: 0,
+            decay_flash_ticks: 0,
+            combo: 1,
+            best_combo: 1,
+            level: 1,
+            banner: None,
+            lives: settings.lives,
+            messages: VecDeque::new(),
+            message_expires: 0,
+            rival: None,
+            outcome: None,
+            min_bound: (margin, margin),
+            max_bound: (width - 1 - margin, height - 1 - margin),
+            mines: HashSet::new(),
+            ticks: 0,
+            random_walls: HashSet::new(),
+            generated_walls: HashSet::new(),
+            rng: match (&settings.seed, daily) {
+                (Some(code), _) => StdRng::seed_from_u64(seed::from_code(code)),
+                (None, Some(date)) => StdRng::seed_from_u64(date.seed()),
+                (None, None) => StdRng::from_os_rng(),
+            },
+            daily,
+            daily_best: daily.and_then(|date| daily::best_score(date, &score_bucket(&settings))),
+            best_score: best::best_score(&score_bucket(&settings)),
+            sprinting: false,
+            brake_ticks: 0,
+            brake_cooldown: 0,
+            grace_tick: false,
+            phases: if settings.phases {
+                Self::PHASE_CHARGES
+            } else {
+                0
+            },
+            phase_flash_ticks: 0,
+            popups: Vec::new(),
+            eat_flash_ticks: 0,
+            konami_code: KonamiCode::default(),
+            history: History::default(),
+            rainbow: false,
+            pending_growth: 0,
+            // Debug games are for trying things out, so they don't unlock anything
+            achievements: if settings.debug {
+                Achievements::locked()
+            } else {
+                Achievements::load()
+            },
+            achievement_banner: None,
+            showing_achievements: false,
+            help_opened: None,
+            paused_at: None,
+            dying: None,
+            too_small: false,
+            countdown: None,
+            colors: colors_supported(),
+            truecolor: truecolor_supported(),
+            glyphs: if settings.ascii || !unicode_supported() {
+                &Glyphs::ASCII
+            } else {
+                &Glyphs::UNICODE
+            },
+            choosing_mode: settings.mode.is_none() && !settings.tutorial,
+            menu_selection: 0,
+            settings_row: None,
+            entering_seed: None,
+            idle_since: Instant::now(),
+            demo: false,
+            choosing_level: mode == Mode::Nokia && settings.speed_level.is_none(),
+            tutorial: settings.tutorial.then(Tutorial::default),
+            debug: settings
+                .debug
+                .then(|| DebugTools::new((width / 2, height / 3))),
+            play_time: Duration::ZERO,
+            time_up: false,
+            won: false,
+            splits: Vec::new(),
+            best_splits: splits::best_splits(&score_bucket(&settings)),
+            settings,
+        };
+
+        game.earthquake_ticks = game.rng.random_range(Self::EARTHQUAKE_TICKS);
+        // Straight into the game, so give the player a moment to get ready
+        if !game.choosing_mode && !game.choosing_level {
+            game.countdown = Some(Instant::now());
         }
-    }
-}
+        if let Some(generator) = game.settings.generator {
+            // Versus games don't start in the middle, so their runway needs clearing too
+            let runway = |(x, y): (u16, u16)| {
+                y == spawn.1 && (spawn.0..=spawn.0 + Self::SPAWN_RUNWAY).contains(&x)
+            };
+            game.generated_walls = generator
+                .generate(width.min(height), &mut game.rng)
+                .into_iter()
+                .filter(|&cell| !runway(cell))
+                .collect();
+        }
+        game.obstacles = game.layout_obstacles();
+        game.obstacles.remove(&spawn);
+        game.snake = game.starting_body(spawn);
+        game.place_random_walls(spawn);
 
-impl Game {
-    const BOARD_SIZE: u16 = 20;
+        if let Some(controller) = game.settings.opponent {
+            // Player two starts mirrored across the middle of the board, facing the other way
+            let mut rival_spawn = (width - 1 - spawn.0, height - 1 - spawn.1);
+            if game.snake.contains(&rival_spawn)
+                || game.obstacles.contains(&rival_spawn)
+                || game.is_portal(rival_spawn)
+            {
+                rival_spawn = game.safe_spawn();
+            }
+            game.rival = Some(Rival {
+                body: vec![rival_spawn],
+                direction: Direction::Left,
+                score: 0,
+                pending_growth: 0,
+                controller,
+                respawn_ticks: 0,
+            });
+        }
+        let rival_body = game.rival.as_ref().map(|rival| rival.body.clone());
+        game.patrollers.retain(|patroller| {
+            !game.obstacles.contains(&patroller.position)
+                && !game.snake.contains(&patroller.position)
+                && rival_body
+                    .as_ref()
+                    .is_none_or(|body| !body.contains(&patroller.position))
+        });
+
+        let first_apple = (width / 2, height / 3);
+        if game.apple_count() > 0
+            && !game.snake.contains(&first_apple)
+            && !game.is_wall(first_apple)
+        {
+            let color = (game.mode() == Mode::Sequence).then_some(AppleColor::ORDER[0]);
+            game.apples.push(Apple {
+                color,
+                ..Apple::new(first_apple)
+            });
+        }
+        game.spawn_apples();
+        // The tutorial puts down its own apple when it gets to it
+        if game.tutorial.is_some() {
+            game.apples.clear();
+        }
+
+        game
+    }
 
     pub fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
         loop {
             while event::poll(Duration::ZERO).is_ok_and(|available| available) {
                 match event::read()? {
                     Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                        self.idle_since = Instant::now();
+                        if !self.choosing_mode
+                            && !self.demo
+                            && !self.choosing_level
+                            && !self.game_over
+                            && self.paused_at.is_none()
+                            && self.help_opened.is_none()
+                            && self.konami_code.press(key_event.code)
+                        {
+                            self.enter_secret_code();
+                        }
+                        let action = self.settings.keymap.action(key_event.code);
                         match key_event.code {
-                            KeyCode::Up | KeyCode::Char('w') => {
-                                if self.is_valid_turn(Direction::Up) {
-                                    self.snake_direction = Direction::Up
-                                }
+                            code if self.entering_seed.is_some() => self.enter_seed(code),
+                            _ if self.help_opened.is_some() => self.close_help(),
+                            _ if action == Some(Action::Quit) && self.tutorial.is_some() => {
+                                self.end_tutorial()
                             }
-                            KeyCode::Down | KeyCode::Char('s') => {
-                                if self.is_valid_turn(Direction::Down) {
-                                    self.snake_direction = Direction::Down
-                                }
+                            _ if action == Some(Action::Quit) => return Ok(()),
+                            // The snake is left as it crashed until it's finished dying
+                            _ if self.dying.is_some() => {}
+                            // The demo is thrown away, nothing about it carries over
+                            _ if self.demo => *self = Self::new(self.settings.clone()),
+                            _ if action == Some(Action::Pause) && self.can_pause() => {
+                                self.toggle_pause();
                             }
-                            KeyCode::Left | KeyCode::Char('a') => {
-                                if self.is_valid_turn(Direction::Left) {
-                                    self.snake_direction = Direction::Left
-                                }
+                            _ if action == Some(Action::Restart) && self.paused_at.is_some() => {
+                                self.restart()
                             }
-                            KeyCode::Right | KeyCode::Char('d') => {
-                                if self.is_valid_turn(Direction::Right) {
-                                    self.snake_direction = Direction::Right
-                                }
+                            // Nothing else happens until the game is resumed
+                            _ if self.paused_at.is_some() => continue,
+                            // Not h as well any more, that steers left
+                            KeyCode::Char('?') if !self.choosing_mode && !self.choosing_level => {
+                                self.help_opened = Some(Instant::now());
                             }
-                            KeyCode::Char('r') => {
-                                if self.game_over {
-                                    *self = Self::default();
+                            code if self.settings_row.is_some() => self.change_setting(code),
+                            code if self.choosing_mode => self.choose_mode(code),
+                            code if self.choosing_level => self.choose_level(code),
+                            code if self.debug.is_some() && self.debug_key(code) => {}
+                            // Hardcore mode doesn't allow stopping mid-game
+                            KeyCode::Char('t')
+                                if self.game_over || self.mode() != Mode::Hardcore =>
+                            {
+                                self.showing_achievements = !self.showing_achievements;
+                            }
+                            KeyCode::Char(key)
+                                if self.has_second_player()
+                                    && let Some(&(_, direction)) = Self::SECOND_PLAYER_KEYS
+                                        .iter()
+                                        .find(|&&(bound, _)| bound == key) =>
+                            {
+                                self.turn_rival(direction)
+                            }
+                            _ if action == Some(Action::Sprint) && !self.game_over => {
+                                self.sprinting = !self.sprinting;
+                            }
+                            _ if action == Some(Action::Brake) && !self.game_over => self.brake(),
+                            // Kept in the settings so it lasts through restarts
+                            KeyCode::Char('g') => self.settings.grid = !self.settings.grid,
+                            KeyCode::Char('u') if self.mode() == Mode::Practice => self.rewind(),
+                            _ if action == Some(Action::Restart) && self.game_over => {
+                                self.restart()
+                            }
+                            KeyCode::Char('m') if self.game_over => {
+                                self.settings.mode = None;
+                                *self = Self::new(self.settings.clone());
+                            }
+                            code => {
+                                if let Some(direction) = self.settings.keymap.direction(code) {
+                                    self.turn(direction);
                                 }
                             }
-                            KeyCode::Char('q') => return Ok(()),
-                            _ => (),
+                        }
+                        if let Some(tutorial) = &mut self.tutorial
+                            && tutorial.press(key_event.code, &self.settings.keymap)
+                        {
+                            self.next_tutorial_step();
                         }
                     }
+                    Event::Resize(width, height) => self.resize(width, height),
+                    Event::Mouse(mouse) if self.settings.mouse => {
+                        let size = terminal.size()?;
+                        self.click(mouse, Rect::new(0, 0, size.width, size.height));
+                    }
                     _ => {}
                 };
             }
 
-            if !self.game_over
-                && self.last_moved.elapsed() > Duration::from_millis(self.snake_move_time)
+            // The snake waits while the banner is up
+            if self
+                .banner
+                .as_ref()
+                .is_some_and(|(_, shown)| shown.elapsed() >= Self::BANNER_TIME)
             {
-                let direction = self.snake_direction.get_vec2();
-                let head = self.snake[0];
+                self.banner = None;
+                self.last_moved = Instant::now();
+            }
 
-                // Snake hit border
-                if (head.0 == 0 && direction.0 < 0)
-                    || (head.1 == 0 && direction.1 < 0)
-                    || (head.0 == Self::BOARD_SIZE - 1 && direction.0 > 0)
-                    || (head.1 == Self::BOARD_SIZE - 1 && direction.1 > 0)
-                {
-                    self.game_over = true;
-                    continue;
-                }
+            // The clock starts from when the countdown ends, not from when it began
+            if self.countdown.is_some_and(|started| {
+                started.elapsed() >= Duration::from_secs(Self::COUNTDOWN_SECONDS)
+            }) {
+                self.countdown = None;
+                self.last_moved = Instant::now();
+            }
 
-                self.snake.pop();
+            if self
+                .dying
+                .is_some_and(|crashed| crashed.elapsed() >= Self::DEATH_TIME)
+            {
+                self.dying = None;
+            }
 
-                let next_head = (
-                    head.0.saturating_add_signed(direction.0),
-                    head.1.saturating_add_signed(direction.1),
-                );
+            if self
+                .achievement_banner
+                .is_some_and(|(_, shown)| shown.elapsed() >= Self::ACHIEVEMENT_BANNER_TIME)
+            {
+                self.achievement_banner = None;
+            }
 
-                // Snake hit itself
-                if self.snake.contains(&next_head) {
-                    self.game_over = true;
-                    continue;
+            if self.choosing_mode
+                && self.entering_seed.is_none()
+                && self.settings_row.is_none()
+                && self.idle_since.elapsed() >= Self::DEMO_IDLE_TIME
+            {
+                self.start_demo();
+            }
+
+            // Checked every frame as well as on resize events, so a rebuilt game picks it up too
+            let size = terminal.size()?;
+            self.resize(size.width, size.height);
+
+            let interval = self.move_interval();
+            if self.moving() && self.last_moved.elapsed() >= interval {
+                // Move the clock on by exactly one interval so that lateness doesn't add up over
+                // many short ticks, unless the game has fallen too far behind to catch up
+                self.last_moved = if self.last_moved.elapsed() >= interval * 2 {
+                    Instant::now()
+                } else {
+                    self.last_moved + interval
+                };
+                if self.demo {
+                    self.snake_direction = self.autopilot_direction();
+                }
+                self.tick();
+                if self.game_over && self.demo {
+                    *self = Self::new(self.settings.clone());
+                    self.start_demo();
+                } else if self.game_over {
+                    self.finish();
+                    if !self.won && !self.time_up {
+                        self.dying = Some(Instant::now());
+                    }
                 }
+            }
 
-                self.snake.insert(0, next_head);
+            terminal.draw(|frame| self.draw(frame))?;
+        }
+    }
 
-                self.last_moved = Instant::now();
+    /// Keeps whatever is worth keeping once the game has ended
+    fn finish(&mut self) {
+        // Nothing from a debug game counts
+        if self.debug.is_some() {
+            return;
+        }
+        let died = !self.time_up && !self.won && self.outcome != Some(Outcome::PlayerOneWins);
+        if died {
+            let unlocked = self.achievements.check_death(self.snake.len());
+            self.show_achievement(unlocked);
+        }
 
-                if self.snake[0] == self.apple_position {
-                    let tail_direction = if self.snake.len() > 1 {
-                        let (x1, y1) = self.snake[self.snake.len() - 1];
-                        let (x2, y2) = self.snake[self.snake.len() - 2];
-                        (x1 as i16 - x2 as i16, y1 as i16 - y2 as i16)
-                    } else {
-                        let (x, y) = self.snake_direction.get_vec2();
-                        (-x, -y)
-                    };
-                    let tail = self.snake[self.snake.len() - 1];
+        if self.tracks_splits() {
+            let best = splits::merge(&self.best_splits, &self.splits);
+            if best != self.best_splits {
+                splits::save_best_splits(&score_bucket(&self.settings), &best).ok();
+            }
+        }
 
-                    self.snake.push((
-                        tail.0.saturating_add_signed(tail_direction.0),
-                        tail.1.saturating_add_signed(tail_direction.1),
-                    ));
+        // Practice scores don't count
+        if self.mode() != Mode::Practice && self.score > self.best_score {
+            best::save_best_score(&score_bucket(&self.settings), self.score).ok();
+        }
+        if let Some(date) = self.daily
+            && self.mode() != Mode::Practice
+            && self.daily_best.is_none_or(|best| self.score > best)
+        {
+            // Failing to save shouldn't get in the way of the results screen
+            daily::save_best_score(date, &score_bucket(&self.settings), self.score).ok();
+        }
+    }
+
+    /// Starts the same game again, keeping the best score so far even if it couldn't be saved. A
+    /// game given up on from the pause screen doesn't count
+    fn restart(&mut self) {
+        let best_score = if !self.game_over || self.mode() == Mode::Practice || self.debug.is_some()
+        {
+            self.best_score
+        } else {
+            self.best_score.max(self.score)
+        };
+        *self = Self::new(self.settings.clone());
+        self.best_score = self.best_score.max(best_score);
+    }
 
-                    let mut possible_positions =
-                        Vec::with_capacity(Self::BOARD_SIZE as usize * Self::BOARD_SIZE as usize);
+    /// The smallest terminal the board and its border fit in, as columns and rows
+    fn min_terminal_size(&self) -> (u16, u16) {
+        ((self.width + 3) * 2, self.height + 3)
+    }
 
-                    for x in 0..Self::BOARD_SIZE {
-                        for y in 0..Self::BOARD_SIZE {
-                            if !self.snake.contains(&(x, y)) {
-                                possible_positions.push((x, y));
-                            }
-                        }
-                    }
+    /// Holds the game while a terminal of `width` by `height` is too small for the board
+    fn resize(&mut self, width: u16, height: u16) {
+        let (min_width, min_height) = self.min_terminal_size();
+        let too_small = width < min_width || height < min_height;
+        // Getting the room back is like resuming from a pause
+        if self.too_small && !too_small && self.can_pause() && self.paused_at.is_none() {
+            self.countdown = Some(Instant::now());
+        }
+        self.too_small = too_small;
+    }
+
+    /// Whether the snake is due to move when its time comes, with nothing holding the game
+    fn moving(&self) -> bool {
+        !self.game_over
+            && !self.too_small
+            && self.help_opened.is_none()
+            && self.paused_at.is_none()
+            && self.countdown.is_none()
+            && !self.choosing_mode
+            && !self.choosing_level
+            && !self.showing_achievements
+            && self.banner.is_none()
+            && !self.tutorial.as_ref().is_some_and(Tutorial::waiting)
+    }
+
+    /// Whether the game can be paused or resumed. Hardcore mode doesn't allow stopping mid-game
+    fn can_pause(&self) -> bool {
+        !self.game_over
+            && !self.choosing_mode
+            && !self.choosing_level
+            && self.mode() != Mode::Hardcore
+    }
+
+    /// Closes the list of controls, carrying on exactly where the game was when it opened
+    fn close_help(&mut self) {
+        let Some(opened) = self.help_opened.take() else {
+            return;
+        };
+        let shown = opened.elapsed();
+        self.last_moved += shown;
+        if let Some(started) = &mut self.countdown {
+            *started += shown;
+        }
+    }
 
-                    self.apple_position = *possible_positions.choose(&mut rand::rng()).unwrap();
-                    self.snake_move_time = (self.snake_move_time - 10).max(50);
+    /// Pauses the game, or resumes it after a countdown
+    fn toggle_pause(&mut self) {
+        match self.paused_at.take() {
+            Some(_) => self.countdown = Some(Instant::now()),
+            None => self.paused_at = Some(Instant::now()),
+        }
+    }
+
+    /// Slows the snake down for a few ticks, as long as the brake has cooled down since it was
+    /// last used
+    fn brake(&mut self) {
+        if self.brake_cooldown > 0 {
+            return;
+        }
+        self.brake_ticks = Self::BRAKE_TICKS;
+        self.brake_cooldown = Self::BRAKE_COOLDOWN_TICKS;
+    }
+
+    /// Sets up whatever the tutorial's new step needs, or starts a real game after the last one
+    fn next_tutorial_step(&mut self) {
+        match self.tutorial.as_ref().and_then(Tutorial::step) {
+            None => self.end_tutorial(),
+            Some(Step::EatApple) => {
+                // Two cells straight ahead, so it's hard to miss
+                let direction = self.snake_direction.get_vec2();
+                let ahead = self
+                    .head_target(self.snake[0], direction)
+                    .and_then(|cell| self.head_target(cell, direction))
+                    .filter(|cell| self.free_cells().contains(cell));
+                match ahead {
+                    Some(cell) => self.apples = vec![Apple::new(cell)],
+                    None => self.spawn_apples(),
                 }
             }
+            Some(_) => (),
+        }
+        self.last_moved = Instant::now();
+    }
 
-            terminal.draw(|frame| self.draw(frame))?;
+    /// Leaves the tutorial for a normal game in the same mode
+    fn end_tutorial(&mut self) {
+        self.settings.tutorial = false;
+        self.settings.mode = Some(self.mode());
+        *self = Self::new(self.settings.clone());
+    }
+
+    /// Turns the snake rainbow and gives a one-off bonus the first time the Konami code is entered
+    fn enter_secret_code(&mut self) {
+        if self.rainbow {
+            return;
         }
+        self.rainbow = true;
+        self.score += Self::SECRET_CODE_BONUS;
+        self.show_message(format!("Rainbow! +{}", Self::SECRET_CODE_BONUS));
+        let achievement = self.achievements.check_secret_code();
+        self.show_achievement(achievement);
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn show_achievement(&mut self, achievement: Option<Achievement>) {
+        if let Some(achievement) = achievement {
+            self.achievement_banner = Some((achievement, Instant::now()));
+        }
     }
 
-    fn is_valid_turn(&self, direction: Direction) -> bool {
-        if self.snake.len() > 1 {
-            let direction = direction.get_vec2();
-            let head = self.snake[0];
+    /// Starts a game in the mode picked on the pre-game screen by pressing its number
+    fn choose_mode(&mut self, code: KeyCode) {
+        let key = match code {
+            KeyCode::Up => {
+                self.menu_selection = (self.menu_selection + Mode::ALL.len() - 1) % Mode::ALL.len();
+                return;
+            }
+            KeyCode::Down => {
+                self.menu_selection = (self.menu_selection + 1) % Mode::ALL.len();
+                return;
+            }
+            KeyCode::Enter => Mode::ALL[self.menu_selection].key(),
+            KeyCode::Tab => {
+                self.settings_row = Some(0);
+                return;
+            }
+            KeyCode::Char(key) => key,
+            _ => return,
+        };
 
-            (
-                head.0.saturating_add_signed(direction.0),
-                head.1.saturating_add_signed(direction.1),
-            ) != self.snake[1]
-        } else {
-            self.snake_direction.opposite() != direction
+        if key == 'g' {
+            self.entering_seed = Some(self.settings.seed.clone().unwrap_or_default());
+            return;
+        }
+        if let Some(mode) = Mode::from_key(key) {
+            self.settings.mode = Some(mode);
+            *self = Self::new(self.settings.clone());
+        } else if let Some(difficulty) = Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().starts_with(key))
+        {
+            // The board is rebuilt so it's the right size behind the menu
+            self.settings.difficulty = difficulty;
+            *self = Self::new(self.settings.clone());
         }
     }
-}
 
-impl Widget for &Game {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        if area.width < (Game::BOARD_SIZE + 3) * 2 || area.height < Game::BOARD_SIZE + 3 {
-            Line::from(format!(
-                "The terminal window must be at least {}x{}",
-                (Game::BOARD_SIZE + 3) * 2,
-                Game::BOARD_SIZE + 3
-            ))
-            .render(area, buf);
+    /// Moves around the settings screen and changes the highlighted setting, going back to the
+    /// pre-game screen with the new settings in place once it's closed
+    fn change_setting(&mut self, code: KeyCode) {
+        let Some(row) = self.settings_row else {
+            return;
+        };
+
+        let rows = SettingsRow::ALL.len();
+        match code {
+            KeyCode::Up => self.settings_row = Some((row + rows - 1) % rows),
+            KeyCode::Down => self.settings_row = Some((row + 1) % rows),
+            KeyCode::Left => SettingsRow::ALL[row].change(&mut self.settings, false),
+            KeyCode::Right => SettingsRow::ALL[row].change(&mut self.settings, true),
+            // The board is rebuilt so it's the right size behind the menu
+            KeyCode::Esc | KeyCode::Tab | KeyCode::Enter => {
+                *self = Self::new(self.settings.clone());
+            }
+            _ => (),
+        }
+    }
+
+    /// Leaves the pre-game screen to play itself, without unlocking or saving anything, until a
+    /// key is pressed
+    fn start_demo(&mut self) {
+        self.demo = true;
+        self.countdown = None;
+        self.choosing_mode = false;
+        self.achievements = Achievements::locked();
+        self.last_moved = Instant::now();
+    }
+
+    /// Types into the seed code, which is used for the board once it's confirmed with enter.
+    /// Confirming an empty code goes back to a random board
+    fn enter_seed(&mut self, code: KeyCode) {
+        let Some(typed) = &mut self.entering_seed else {
             return;
+        };
+
+        match code {
+            KeyCode::Char(c) if seed::is_code_char(c) && typed.len() < seed::MAX_CODE_LENGTH => {
+                typed.push(c);
+            }
+            KeyCode::Backspace => {
+                typed.pop();
+            }
+            KeyCode::Esc => self.entering_seed = None,
+            KeyCode::Enter => {
+                self.settings.seed = Some(typed.clone()).filter(|typed| !typed.is_empty());
+                *self = Self::new(self.settings.clone());
+            }
+            _ => (),
         }
+    }
 
-        // Roughly 1x2 terminal character size
-        let board_rect = Rect::new(
-            area.x + ((area.width / 2).saturating_sub(Game::BOARD_SIZE)),
-            area.y + ((area.height / 2).saturating_sub(Game::BOARD_SIZE / 2)),
-            (Game::BOARD_SIZE - 1) * 2,
-            Game::BOARD_SIZE,
-        );
+    /// Starts a Nokia game at the speed picked by pressing its number
+    fn choose_level(&mut self, code: KeyCode) {
+        if let KeyCode::Char(key) = code
+            && let Some(level) = key.to_digit(10)
+            && (1..=Settings::MAX_SPEED_LEVEL).contains(&level)
+        {
+            self.settings.speed_level = Some(level);
+            *self = Self::new(self.settings.clone());
+        }
+    }
 
-        let border_rect = Rect::new(
-            board_rect.x - 1,
-            board_rect.y - 1,
-            board_rect.width + 4,
-            board_rect.height + 2,
-        );
+    fn mode(&self) -> Mode {
+        self.settings.mode.unwrap_or_default()
+    }
 
-        Block::bordered()
-            .border_type(ratatui::widgets::BorderType::Plain)
-            .border_set(border::THICK)
-            .title(Line::from(format!(" Score: {} ", self.snake.len() - 1)).centered())
-            .render(border_rect, buf);
+    /// Time left on the clock in timed modes
+    fn time_left(&self) -> Option<Duration> {
+        match self.mode() {
+            Mode::Classic
+            | Mode::Zen
+            | Mode::Hardcore
+            | Mode::Tron
+            | Mode::Shrinking
+            | Mode::Evasive
+            | Mode::Practice
+            | Mode::Nokia
+            | Mode::Sequence
+            | Mode::Invisible => None,
+            Mode::TimeAttack => Some(Self::TIME_ATTACK_DURATION.saturating_sub(self.play_time)),
+            Mode::Frenzy => Some(Self::FRENZY_DURATION.saturating_sub(self.play_time)),
+        }
+    }
 
-        if self.game_over {
-            Text::from("\n\n\n\nGame Over\n\nPress r to restart\nPress q to quit")
-                .centered()
-                .render(border_rect, buf);
-        } else {
-            let (x, y) = self.apple_position;
-            buf[((x * 2) + board_rect.x, y + board_rect.y)].set_symbol("##");
+    /// Moves the snake one cell and applies whatever it runs into
+    fn tick(&mut self) {
+        let previous_score = self.score;
+        if self.mode() == Mode::Practice {
+            self.history.push(Snapshot {
+                snake: self.snake.clone(),
+                direction: self.snake_direction,
+                apples: self.apples.clone(),
+                patrollers: self.patrollers.clone(),
+                move_time: self.snake_move_time,
+                score: self.score,
+                pending_growth: self.pending_growth,
+                pickups: self.pickups.clone(),
+                mega_apple: self.mega_apple.clone(),
+                mines: self.mines.clone(),
+                bounds: (self.min_bound, self.max_bound),
+                apples_eaten: self.apples_eaten,
+                ticks_since_apple: self.ticks_since_apple,
+                combo: self.combo,
+                lives: self.lives,
+                phases: self.phases,
+                ticks: self.ticks,
+                play_time: self.play_time,
+                effects: Effects {
+                    boost: self.boost_ticks,
+                    slow: self.slow_ticks,
+                    freeze: self.freeze_ticks,
+                    magnet: self.magnet_ticks,
+                    ghost: self.ghost_ticks,
+                    ghost_grace_used: self.ghost_grace_used,
+                    reversed: self.reversed_ticks,
+                    brake: self.brake_ticks,
+                    brake_cooldown: self.brake_cooldown,
+                    sprinting: self.sprinting,
+                },
+            });
+        }
+        self.advance();
 
-            for (x, y) in &self.snake {
-                buf[((x * 2) + board_rect.x, y + board_rect.y)].set_symbol("██");
+        if self.tracks_splits() {
+            while self.splits.len() < (self.score / splits::SPLIT_SCORE) as usize {
+                self.splits.push(self.play_time);
+            }
+        }
+        if self.mode() != Mode::Tron {
+            let mut milestone = next_milestone(previous_score);
+            while milestone <= self.score {
+                self.show_message(format!("Nice! {milestone}"));
+                milestone = next_milestone(milestone);
             }
         }
+        if !self.messages.is_empty() && self.ticks >= self.message_expires {
+            self.messages.pop_front();
+            self.message_expires = self.ticks + self.message_ticks();
+        }
     }
-}
 
-#[derive(Debug, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+    /// Puts the game back how it was a tick ago, even if the snake has since crashed
+    fn rewind(&mut self) {
+        let Some(snapshot) = self.history.pop() else {
+            return;
+        };
+        self.snake = snapshot.snake;
+        self.snake_direction = snapshot.direction;
+        self.apples = snapshot.apples;
+        self.patrollers = snapshot.patrollers;
+        self.snake_move_time = snapshot.move_time;
+        self.score = snapshot.score;
+        self.pending_growth = snapshot.pending_growth;
+        self.pickups = snapshot.pickups;
+        self.mega_apple = snapshot.mega_apple;
+        self.mines = snapshot.mines;
+        (self.min_bound, self.max_bound) = snapshot.bounds;
+        self.apples_eaten = snapshot.apples_eaten;
+        self.ticks_since_apple = snapshot.ticks_since_apple;
+        self.combo = snapshot.combo;
+        self.lives = snapshot.lives;
+        self.phases = snapshot.phases;
+        self.ticks = snapshot.ticks;
+        self.play_time = snapshot.play_time;
+        let effects = snapshot.effects;
+        self.boost_ticks = effects.boost;
+        self.slow_ticks = effects.slow;
+        self.freeze_ticks = effects.freeze;
+        self.magnet_ticks = effects.magnet;
+        self.ghost_ticks = effects.ghost;
+        self.ghost_grace_used = effects.ghost_grace_used;
+        self.reversed_ticks = effects.reversed;
+        self.brake_ticks = effects.brake;
+        self.brake_cooldown = effects.brake_cooldown;
+        self.sprinting = effects.sprinting;
+        self.game_over = false;
+        self.outcome = None;
+        self.last_moved = Instant::now();
+    }
 
-impl Direction {
-    pub fn get_vec2(&self) -> (i16, i16) {
-        match self {
-            Self::Up => (0, -1),
-            Self::Down => (0, 1),
-            Self::Left => (-1, 0),
-            Self::Right => (1, 0),
+    /// Whether split times are kept, which needs a score that goes up with apples and a game
+    /// that counts
+    fn tracks_splits(&self) -> bool {
+        !matches!(self.mode(), Mode::Tron | Mode::Practice)
+    }
+
+    /// Queues `message` to show over the board after any that are already waiting
+    fn show_message(&mut self, message: String) {
+        if self.messages.is_empty() {
+            self.message_expires = self.ticks + self.message_ticks();
         }
+        self.messages.push_back(message);
     }
 
-    pub fn opposite(&self) -> Direction {
-        match self {
-            Self::Up => Self::Down,
-            Self::Down => Self::Up,
-            Self::Left => Self::Right,
-            Self::Right => Self::Left,
+    /// Roughly how many ticks a message stays up for at the current speed
+    fn message_ticks(&self) -> u32 {
+        (Self::MESSAGE_TIME.as_millis() / self.move_interval().as_millis().max(1)) as u32
+    }
+
+    /// Everything that happens in a tick apart from messages, which can stop early once the
+    /// tick's outcome is settled
+    fn advance(&mut self) {
+        self.play_time += self.move_interval();
+        if self.border_mode() == BorderMode::Bounce {
+            self.bounce();
+        }
+        self.phase_flash_ticks = self.phase_flash_ticks.saturating_sub(1);
+        self.eat_flash_ticks = self.eat_flash_ticks.saturating_sub(1);
+        for popup in &mut self.popups {
+            popup.rise();
+        }
+        self.popups.retain(|popup| popup.ticks_left > 0);
+        let ahead = self.head_target(self.snake[0], self.snake_direction.get_vec2());
+        // A phase charge carries the head through the border, but it's only used up once the
+        // snake has actually made the move
+        let phasing = ahead.is_none() && self.phases > 0;
+        let next_head = ahead
+            .or_else(|| phasing.then(|| self.wrapped_ahead()))
+            // God mode goes straight through the border instead of dying on it
+            .or_else(|| self.god_mode().then(|| self.wrapped_ahead()))
+            .filter(|&cell| self.mode() == Mode::Zen || self.god_mode() || !self.is_wall(cell));
+
+        // Forgiveness holds everything still for a tick instead of crashing straight away
+        if self.settings.forgiving && !self.grace_tick && self.crashes_into_self_or_wall(next_head)
+        {
+            self.grace_tick = true;
+            return;
+        }
+        self.grace_tick = false;
+
+        if self.rival.is_some() && !self.move_rival(next_head) {
+            return;
+        }
+
+        // Snake hit border or an obstacle
+        let Some(next_head) = next_head else {
+            self.die();
+            return;
+        };
+
+        // Growing just means the tail stays put for a move
+        if self.pending_growth > 0 {
+            self.pending_growth -= 1;
+        } else if !self.keeps_trail() {
+            self.snake.pop();
+        }
+
+        // Snake hit itself
+        if self.ghost_ticks == 0
+            && self.mode() != Mode::Zen
+            && !self.god_mode()
+            && self.snake.contains(&next_head)
+        {
+            self.die();
+            return;
+        }
+
+        if phasing {
+            self.phases -= 1;
+            self.phase_flash_ticks = Self::PHASE_FLASH_TICKS;
+        }
+        self.snake.insert(0, next_head);
+
+        let unlocked = self.achievements.check_move(self.snake.len(), self.ticks);
+        self.show_achievement(unlocked);
+        // Either snake can take the last cell, whether or not there was an apple in it
+        if self.board_filled() {
+            self.won = true;
+            self.game_over = true;
+            return;
+        }
+
+        let score_before = self.score;
+        let eaten = self
+            .apples
+            .iter()
+            .position(|apple| apple.position == self.snake[0])
+            .map(|index| self.apples.swap_remove(index));
+        // Only one apple of a choice can be had
+        if let Some(choice) = eaten.as_ref().and_then(|apple| apple.choice) {
+            self.apples.retain(|apple| apple.choice != Some(choice));
+        }
+        if let Some(apple) = &eaten
+            && apple
+                .color
+                .is_some_and(|color| color != AppleColor::ORDER[self.next_color])
+        {
+            // Out of order, so it costs a point and moves somewhere else
+            self.score = self.score.saturating_sub(Self::SEQUENCE_PENALTY);
+            if let Some(position) = self.apple_cell() {
+                self.apples.push(Apple {
+                    color: apple.color,
+                    ..Apple::new(position)
+                });
+            }
+        } else if let Some(apple) = &eaten
+            && self.apple_state(apple) == AppleState::Rotten
+        {
+            self.score = self.score.saturating_sub(Self::ROTTEN_APPLE_PENALTY);
+            self.spawn_apples();
+        } else if let Some(apple) = eaten {
+            // An apple's pair goes with it if it's right next door, otherwise it's left as a
+            // normal apple
+            let twin = apple.pair.and_then(|pair| {
+                let index = self
+                    .apples
+                    .iter()
+                    .position(|other| other.pair == Some(pair))?;
+                let (x, y) = self.apples[index].position;
+                if x.abs_diff(apple.position.0) + y.abs_diff(apple.position.1) == 1 {
+                    Some(self.apples.swap_remove(index))
+                } else {
+                    self.apples[index].pair = None;
+                    None
+                }
+            });
+
+            let unlocked = self
+                .achievements
+                .check_apple((self.apples_eaten > 0).then_some(self.ticks_since_apple));
+            self.show_achievement(unlocked);
+
+            self.pending_growth += self.settings.growth_per_apple;
+            self.update_combo();
+            self.score += if apple.premium {
+                self.apple_value() * Self::PREMIUM_APPLE_VALUE
+            } else {
+                self.apple_value()
+            };
+            if twin.is_some() {
+                self.pending_growth += self.settings.growth_per_apple;
+                self.score += self.apple_value();
+            }
+            // Sprinting is riskier, so it's worth a little more but only lasts one apple
+            if self.sprinting {
+                self.score += Self::SPRINT_BONUS;
+                self.sprinting = false;
+            }
+            self.ticks_since_apple = 0;
+            // Another apple straight after just starts the flash over
+            self.eat_flash_ticks = Self::EAT_FLASH_TICKS;
+            if apple.color.is_some() {
+                // A new round starts after the last color
+                self.next_color = (self.next_color + 1) % AppleColor::ORDER.len();
+            }
+            if let Some(tutorial) = &mut self.tutorial {
+                tutorial.ate_apple();
+            }
+
+            self.spawn_apples();
+            // Twins count as two, so nothing that comes every so many apples is skipped over
+            let mut levelled_up = false;
+            for _ in 0..1 + usize::from(twin.is_some()) {
+                levelled_up |= self.count_apple();
+            }
+            if levelled_up {
+                return;
+            }
+        }
+
+        if let Some(index) = self
+            .pickups
+            .iter()
+            .position(|pickup| pickup.position == self.snake[0])
+        {
+            match self.pickups.swap_remove(index).kind {
+                PickupKind::Poison => {
+                    // Losing two segments from a snake this short leaves nothing
+                    if self.snake.len() <= 2 && self.mode() != Mode::Zen && !self.god_mode() {
+                        self.die();
+                        return;
+                    }
+                    self.snake
+                        .truncate(self.snake.len().saturating_sub(2).max(1));
+                    self.score = self.score.saturating_sub(2);
+                }
+                PickupKind::Golden => {
+                    self.pending_growth += 1;
+                    self.score += 5;
+                }
+                PickupKind::Bonus => self.score += 10,
+                PickupKind::Reverse => self.reverse_snake(),
+                // A second boost just refreshes the first
+                PickupKind::SpeedBoost => self.boost_ticks = Self::BOOST_TICKS,
+                PickupKind::SlowDown => self.slow_ticks = Self::SLOW_TICKS,
+                PickupKind::Magnet => self.magnet_ticks = Self::MAGNET_TICKS,
+                PickupKind::Freeze => self.freeze_ticks = Self::FREEZE_TICKS,
+                // Like the boost, a second curse only refreshes the first
+                PickupKind::Curse => self.reversed_ticks = Self::REVERSED_TICKS,
+                PickupKind::Decoy => {
+                    self.score = self.score.saturating_sub(Self::DECOY_PENALTY);
+                    self.show_message(format!("Decoy! -{}", Self::DECOY_PENALTY));
+                }
+                PickupKind::Shrink => {
+                    let length = self.snake.len().saturating_sub(Self::SHRINK_SEGMENTS);
+                    self.snake.truncate(length.max(Self::SHRINK_MIN_LENGTH));
+                }
+                PickupKind::Ghost => {
+                    self.ghost_ticks = Self::GHOST_TICKS;
+                    self.ghost_grace_used = false;
+                }
+            }
+        }
+        if self.score > score_before {
+            self.popups
+                .push(Popup::new(self.snake[0], self.score - score_before));
+        }
+
+        if self
+            .mega_apple
+            .as_ref()
+            .is_some_and(|mega_apple| mega_apple.contains(self.snake[0]))
+        {
+            self.mega_apple = None;
+            self.pending_growth += Self::MEGA_APPLE_GROWTH;
+            self.score += Self::MEGA_APPLE_SCORE;
+        }
+
+        self.boost_ticks = self.boost_ticks.saturating_sub(1);
+        self.slow_ticks = self.slow_ticks.saturating_sub(1);
+        self.freeze_ticks = self.freeze_ticks.saturating_sub(1);
+        self.reversed_ticks = self.reversed_ticks.saturating_sub(1);
+        self.brake_ticks = self.brake_ticks.saturating_sub(1);
+        self.brake_cooldown = self.brake_cooldown.saturating_sub(1);
+        self.ticks_since_apple += 1;
+        if self.ticks_since_apple > self.combo_window() {
+            self.combo = 1;
+        }
+        self.decay_score();
+        self.update_ghost();
+        self.update_apples();
+        if self.mode() == Mode::Evasive {
+            self.evade();
+        }
+        if self.magnet_ticks > 0 {
+            self.magnet_ticks -= 1;
+            if self.magnet_ticks.is_multiple_of(Self::MAGNET_DRIFT_TICKS) {
+                self.drift_apples();
+            }
+        }
+        if self.settings.earthquakes {
+            self.update_earthquake();
+        }
+        self.update_mega_apple();
+        self.update_pickups();
+
+        self.ticks += 1;
+        if self.settings.mines
+            && self.time_left().is_some()
+            && self.ticks.is_multiple_of(Self::TICKS_PER_MINE)
+        {
+            self.place_mine();
+        }
+
+        if self.mode() == Mode::Shrinking && self.ticks.is_multiple_of(Self::TICKS_PER_CONTRACTION)
+        {
+            self.contract_arena();
+        }
+
+        if self.ticks.is_multiple_of(Self::PATROL_TICKS) {
+            self.move_patrollers();
         }
+
+        if self.mode() == Mode::Tron {
+            self.score += 1;
+            if !self.god_mode() && !self.has_legal_move() {
+                self.game_over = true;
+            }
+        }
+
+        if self.time_left() == Some(Duration::ZERO) {
+            self.game_over = true;
+            self.time_up = true;
+        }
+    }
+
+    /// Turns the snake around so the tail leads, heading the way the old tail was pointing
+    fn reverse_snake(&mut self) {
+        self.snake.reverse();
+
+        let pointing = match self.snake[..] {
+            [head, neck, ..] => Direction::ALL
+                .into_iter()
+                .find(|direction| self.step(neck, direction.get_vec2()) == Some(head)),
+            _ => None,
+        };
+        // Segments stacked on a single cell or on both sides of a portal don't point anywhere
+        self.snake_direction = pointing.unwrap_or(self.snake_direction.opposite());
+    }
+
+    /// Turns the snake away from the edge of the board if it's about to go over it, clockwise if
+    /// that's clear and counter-clockwise if not. A cornered snake keeps going and crashes
+    fn bounce(&mut self) {
+        let head = self.snake[0];
+        if self.step(head, self.snake_direction.get_vec2()).is_some() {
+            return;
+        }
+
+        let turns = [
+            self.snake_direction.clockwise(),
+            self.snake_direction.counter_clockwise(),
+        ];
+        if let Some(direction) = turns.into_iter().find(|direction| {
+            let next_head = self
+                .head_target(head, direction.get_vec2())
+                .filter(|&cell| !self.is_wall(cell));
+            !self.crashes_into_self_or_wall(next_head)
+        }) {
+            self.snake_direction = direction;
+        }
+    }
+
+    /// Lays the snake out from `spawn` to the left, behind its starting direction, stopping
+    /// early at the edge of the board or anything in the way
+    fn starting_body(&self, spawn: (u16, u16)) -> Vec<(u16, u16)> {
+        let mut body = vec![spawn];
+        while body.len() < self.settings.starting_length {
+            let (x, y) = body[body.len() - 1];
+            let Some(cell) = x.checked_sub(1).map(|x| (x, y)) else {
+                break;
+            };
+            if self.obstacles.contains(&cell) || self.is_portal(cell) || !self.in_arena(cell) {
+                break;
+            }
+            body.push(cell);
+        }
+
+        body
+    }
+
+    /// The cell ahead of the head, wrapping around to the other side of the board at the border
+    fn wrapped_ahead(&self) -> (u16, u16) {
+        let (dx, dy) = self.snake_direction.get_vec2();
+        let (x, y) = self.snake[0];
+        self.wrap_into_arena(x as i16 + dx, y as i16 + dy)
+    }
+
+    /// Costs the snake a life and brings it back somewhere safe, or ends the game on the last one
+    fn die(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            self.game_over = true;
+            return;
+        }
+        self.banner = Some(("Life lost".to_string(), Instant::now()));
+
+        // Like after a level up, the body unfolds from the spawn cell as the snake moves
+        let spawn = self.safe_spawn();
+        let body = std::mem::replace(&mut self.snake, vec![spawn; Self::RESPAWN_LENGTH]);
+        self.drop_apples(&body);
+        self.snake_direction = Direction::Right;
+        self.pending_growth = 0;
+        self.ghost_ticks = 0;
+        self.reversed_ticks = 0;
+        self.sprinting = false;
+        self.snake_move_time = (self.snake_move_time
+            + Self::RESPAWN_SLOWDOWN_STEPS * self.settings.difficulty.speed_step())
+        .min(Self::start_move_time(&self.settings));
+    }
+
+    /// Turns every few segments of a dead snake's `body` into apples to win some length back
+    /// with, clearing away any left over from the last life
+    fn drop_apples(&mut self, body: &[(u16, u16)]) {
+        self.apples.retain(|apple| !apple.dropped);
+
+        let free = self.free_cells();
+        let dropped: Vec<_> = body
+            .iter()
+            .skip(Self::SEGMENTS_PER_DROPPED_APPLE - 1)
+            .step_by(Self::SEGMENTS_PER_DROPPED_APPLE)
+            .filter(|cell| free.contains(cell))
+            .map(|&cell| Apple {
+                dropped: true,
+                ..Apple::new(cell)
+            })
+            .collect();
+        self.apples.extend(dropped);
+    }
+
+    /// Whether moving the head to `next_head` runs into a wall or the snake's own body
+    fn crashes_into_self_or_wall(&self, next_head: Option<(u16, u16)>) -> bool {
+        let tail_moves = self.pending_growth == 0 && !self.keeps_trail();
+        let body = &self.snake[..self.snake.len() - usize::from(tail_moves)];
+
+        next_head.is_none_or(|head| {
+            self.ghost_ticks == 0
+                && self.mode() != Mode::Zen
+                && !self.god_mode()
+                && body.contains(&head)
+        })
+    }
+
+    /// Whether snakes leave a permanent trail instead of moving their tail along
+    fn keeps_trail(&self) -> bool {
+        self.mode() == Mode::Tron
+    }
+
+    /// Whether the snake can move anywhere without crashing next tick
+    fn has_legal_move(&self) -> bool {
+        Direction::ALL.into_iter().any(|direction| {
+            self.is_valid_turn(direction)
+                && self
+                    .head_target(self.snake[0], direction.get_vec2())
+                    .is_some_and(|cell| {
+                        !self.is_wall(cell)
+                            && !self.snake.contains(&cell)
+                            && !self
+                                .rival
+                                .as_ref()
+                                .is_some_and(|rival| rival.body.contains(&cell))
+                    })
+        })
+    }
+
+    /// How many cells of the board the snake could ever fill
+    fn open_cells(&self) -> usize {
+        let portals = if self.portals.is_some() { 2 } else { 0 };
+        self.width as usize * self.height as usize - self.obstacles.len() - portals
+    }
+
+    /// Whether the snakes cover every cell they could ever move to
+    fn board_filled(&self) -> bool {
+        (0..self.width)
+            .flat_map(|x| (0..self.height).map(move |y| (x, y)))
+            .filter(|&cell| !self.is_wall(cell) && !self.is_portal(cell))
+            .all(|cell| {
+                self.snake.contains(&cell)
+                    || self
+                        .rival
+                        .as_ref()
+                        .is_some_and(|rival| rival.body.contains(&cell))
+            })
+    }
+
+    /// Counts one more apple eaten by the player, along with everything that comes every so many
+    /// apples. Returns whether that was the last apple of the level
+    fn count_apple(&mut self) -> bool {
+        self.apples_eaten += 1;
+        if self.settings.phases
+            && self
+                .apples_eaten
+                .is_multiple_of(Self::APPLES_PER_PHASE_REFILL)
+        {
+            self.phases = Self::PHASE_CHARGES;
+        }
+        self.speed_up();
+
+        if self.apples_eaten.is_multiple_of(Self::APPLES_PER_GOLDEN) {
+            self.spawn_pickup(PickupKind::Golden);
+        }
+        if self.apples_eaten.is_multiple_of(Self::APPLES_PER_BONUS) {
+            self.spawn_pickup(PickupKind::Bonus);
+        }
+        // Timed games drop mines on a clock instead
+        if self.settings.mines
+            && self.time_left().is_none()
+            && self.apples_eaten.is_multiple_of(Self::APPLES_PER_MINE)
+        {
+            self.place_mine();
+        }
+
+        if self.settings.growing && self.apples_eaten.is_multiple_of(Self::APPLES_PER_GROWTH) {
+            self.expand_arena();
+        }
+
+        if self.settings.levels && self.apples_eaten.is_multiple_of(Self::APPLES_PER_LEVEL) {
+            self.level_up();
+            return true;
+        }
+
+        false
+    }
+
+    /// Moves the second snake at the same time as the first one, which is about to move to
+    /// `player_head`. Returns whether the game goes on
+    fn move_rival(&mut self, player_head: Option<(u16, u16)>) -> bool {
+        let Some(mut rival) = self.rival.take() else {
+            return true;
+        };
+
+        if rival.body.is_empty() {
+            rival.respawn_ticks = rival.respawn_ticks.saturating_sub(1);
+            if rival.respawn_ticks == 0 {
+                // Keep clear of the player so the respawn can't cause an instant crash
+                let cells: Vec<_> = self
+                    .free_cells()
+                    .into_iter()
+                    .filter(|cell| {
+                        cell.0.abs_diff(self.snake[0].0) + cell.1.abs_diff(self.snake[0].1) > 2
+                    })
+                    .collect();
+                rival.body.extend(cells.choose(&mut self.rng));
+            }
+            self.rival = Some(rival);
+            return true;
+        }
+
+        if rival.controller == Controller::Computer {
+            rival.direction = self.computer_direction(&rival);
+        }
+
+        let rival_head = self
+            .head_target(rival.body[0], rival.direction.get_vec2())
+            .filter(|&cell| !self.is_wall(cell));
+
+        // Both tails move out of the way before the heads move in, unless they're growing
+        let tail_moves =
+            |pending_growth: usize| usize::from(pending_growth == 0 && !self.keeps_trail());
+        let player_body = &self.snake[..self.snake.len() - tail_moves(self.pending_growth)];
+        let rival_body = &rival.body[..rival.body.len() - tail_moves(rival.pending_growth)];
+        let crashes = |head: Option<(u16, u16)>,
+                       other_head: Option<(u16, u16)>,
+                       body: &[(u16, u16)],
+                       other_body: &[(u16, u16)]| {
+            head.is_none_or(|head| {
+                body.contains(&head) || other_body.contains(&head) || Some(head) == other_head
+            })
+        };
+        // A ghost snake passes through itself but not the other snake
+        let own_body = if self.ghost_ticks > 0 {
+            &[][..]
+        } else {
+            player_body
+        };
+        let player_crashed = self.mode() != Mode::Zen
+            && !self.god_mode()
+            && crashes(player_head, rival_head, own_body, rival_body);
+        let rival_crashed = crashes(rival_head, player_head, rival_body, player_body);
+
+        match rival.controller {
+            Controller::Human => {
+                self.outcome = match (player_crashed, rival_crashed) {
+                    (false, false) => None,
+                    (false, true) => Some(Outcome::PlayerOneWins),
+                    (true, false) => Some(Outcome::PlayerTwoWins),
+                    (true, true) => Some(Outcome::Draw),
+                };
+                if self.outcome.is_some() {
+                    self.game_over = true;
+                    self.rival = Some(rival);
+                    return false;
+                }
+            }
+            Controller::Partner | Controller::Twin if player_crashed || rival_crashed => {
+                self.game_over = true;
+                self.rival = Some(rival);
+                return false;
+            }
+            Controller::Partner | Controller::Twin => {}
+            // Only the player's own death ends the game
+            Controller::Computer if player_crashed => {
+                self.rival = Some(rival);
+                self.die();
+                return false;
+            }
+            Controller::Computer if rival_crashed => {
+                rival.body.clear();
+                rival.pending_growth = 0;
+                rival.respawn_ticks = Self::RIVAL_RESPAWN_TICKS;
+                self.rival = Some(rival);
+                return true;
+            }
+            Controller::Computer => {}
+        }
+
+        let Some(rival_head) = rival_head else {
+            self.rival = Some(rival);
+            return true;
+        };
+        if rival.pending_growth > 0 {
+            rival.pending_growth -= 1;
+        } else if !self.keeps_trail() {
+            rival.body.pop();
+        }
+        rival.body.insert(0, rival_head);
+
+        let apple = self
+            .apples
+            .iter()
+            .position(|apple| apple.position == rival_head);
+        if let Some(apple) = apple {
+            self.apples.swap_remove(apple);
+            rival.pending_growth += self.settings.growth_per_apple;
+            if rival.controller.is_teammate() {
+                self.score += self.apple_value();
+            } else {
+                rival.score += 1;
+            }
+        }
+
+        self.rival = Some(rival);
+        if apple.is_some() {
+            self.spawn_apples();
+            self.speed_up();
+        }
+
+        true
+    }
+
+    /// Asks the AI where the computer snake should go next
+    fn computer_direction(&self, rival: &Rival) -> Direction {
+        let apples: Vec<_> = self.apples.iter().map(|apple| apple.position).collect();
+
+        ai::choose_direction(
+            rival.body[0],
+            rival.direction,
+            &apples,
+            |cell, direction| self.head_target(cell, direction.get_vec2()),
+            |cell| self.is_wall(cell) || self.snake.contains(&cell) || rival.body.contains(&cell),
+        )
+    }
+
+    /// Where the demo steers the snake, the same way the computer steers its own
+    fn autopilot_direction(&self) -> Direction {
+        let apples: Vec<_> = self.apples.iter().map(|apple| apple.position).collect();
+
+        ai::choose_direction(
+            self.snake[0],
+            self.snake_direction,
+            &apples,
+            |cell, direction| self.head_target(cell, direction.get_vec2()),
+            |cell| {
+                self.is_wall(cell)
+                    || self.snake.contains(&cell)
+                    || self
+                        .rival
+                        .as_ref()
+                        .is_some_and(|rival| rival.body.contains(&cell))
+            },
+        )
+    }
+
+    /// Counts down the ghost effect. If it runs out with the head inside the body the snake
+    /// gets one more tick to get clear
+    fn update_ghost(&mut self) {
+        if self.ghost_ticks == 0 {
+            return;
+        }
+
+        self.ghost_ticks -= 1;
+        if self.ghost_ticks == 0
+            && !self.ghost_grace_used
+            && self.snake[1..].contains(&self.snake[0])
+        {
+            self.ghost_ticks = 1;
+            self.ghost_grace_used = true;
+        }
+    }
+
+    /// Extends the combo if this apple came quickly enough after the last one
+    fn update_combo(&mut self) {
+        if !self.settings.combos {
+            return;
+        }
+
+        if self.apples_eaten > 0 && self.ticks_since_apple <= self.combo_window() {
+            self.combo += 1;
+        } else {
+            self.combo = 1;
+        }
+        self.best_combo = self.best_combo.max(self.combo);
+    }
+
+    /// Move ticks allowed between apples to keep a combo going. This is a fixed amount of real
+    /// time, so the window grows in ticks as the snake gets faster
+    fn combo_window(&self) -> u32 {
+        (Self::COMBO_WINDOW_MS / self.snake_move_time) as u32
+    }
+
+    /// Points for the apple that was just eaten
+    fn apple_value(&self) -> u32 {
+        let mut value = self.combo;
+        if self.mode() == Mode::Nokia {
+            value *= Self::NOKIA_POINTS_PER_LEVEL * self.settings.speed_level.unwrap_or(1);
+        }
+        if self.settings.speed_scoring {
+            value *= speed_value(self.snake_move_time);
+        }
+        if self.settings.length_scoring {
+            value *= length_value(self.snake.len());
+        }
+        if self.settings.unbounded_speed && self.snake_move_time < Self::MIN_MOVE_TIME {
+            value *= 2;
+        }
+
+        value
+    }
+
+    fn speed_up(&mut self) {
+        // Nokia mode stays at the speed picked before the game, and nothing speeds up while time
+        // is frozen
+        if self.mode() == Mode::Nokia || self.freeze_ticks > 0 {
+            return;
+        }
+        let min_move_time = self.min_move_time();
+
+        // Past the usual top speed the snake keeps speeding up, just more slowly
+        if self.settings.unbounded_speed
+            && !matches!(self.mode(), Mode::Zen | Mode::Frenzy)
+            && self.snake_move_time <= min_move_time
+        {
+            self.snake_move_time = self
+                .snake_move_time
+                .saturating_sub(Self::UNBOUNDED_SPEED_STEP)
+                .max(Self::UNBOUNDED_MIN_MOVE_TIME);
+            return;
+        }
+
+        let curve = self
+            .settings
+            .speed_curve
+            .or_else(|| {
+                self.settings
+                    .generator
+                    .and_then(|generator| generator.speed_curve())
+            })
+            .unwrap_or(match self.mode() {
+                Mode::Hardcore => SpeedCurve::Linear {
+                    step: Self::HARDCORE_SPEED_STEP,
+                },
+                _ => SpeedCurve::Linear {
+                    step: self.settings.difficulty.speed_step(),
+                },
+            });
+        self.snake_move_time =
+            curve.next_speed(self.snake_move_time, self.apples_eaten, min_move_time);
+    }
+
+    fn start_move_time(settings: &Settings) -> u64 {
+        match settings.mode.unwrap_or_default() {
+            Mode::Hardcore => Self::HARDCORE_START_MOVE_TIME,
+            Mode::Frenzy => Self::FRENZY_MOVE_TIME,
+            Mode::Nokia => Self::NOKIA_MOVE_TIMES[settings.speed_level.unwrap_or(1) as usize - 1],
+            _ => settings
+                .start_speed
+                .unwrap_or_else(|| settings.difficulty.start_move_time()),
+        }
+    }
+
+    /// The fastest the snake can get in the current mode
+    fn min_move_time(&self) -> u64 {
+        if let Some(floor) = self.settings.speed_floor
+            && self.mode() != Mode::Frenzy
+        {
+            return floor;
+        }
+
+        match self.mode() {
+            Mode::Zen => Self::ZEN_MOVE_TIME,
+            Mode::Hardcore => Self::HARDCORE_MIN_MOVE_TIME,
+            // The speed never changes in frenzy mode
+            Mode::Frenzy => Self::FRENZY_MOVE_TIME,
+            Mode::Classic
+            | Mode::TimeAttack
+            | Mode::Tron
+            | Mode::Shrinking
+            | Mode::Evasive
+            | Mode::Practice
+            | Mode::Sequence
+            | Mode::Invisible => self.settings.difficulty.min_move_time(),
+            Mode::Nokia => Self::start_move_time(&self.settings),
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    /// Moves on to the next level with more walls, placing the snake somewhere safe
+    fn level_up(&mut self) {
+        self.level += 1;
+        // The board is laid out again, so there's nothing to go back to
+        self.history = History::default();
+        self.banner = Some((format!("LEVEL {}", self.level), Instant::now()));
+        self.snake_move_time = (self.snake_move_time + Self::LEVEL_SLOWDOWN)
+            .min(Self::start_move_time(&self.settings));
+
+        self.obstacles = self.layout_obstacles();
+        self.obstacles
+            .extend(layout::level_obstacles(self.level, self.width, self.height));
+        self.patrollers
+            .retain(|patroller| !self.obstacles.contains(&patroller.position));
+
+        // The body is stacked on the spawn cell and unfolds behind the head as it moves
+        let spawn = self.safe_spawn();
+        self.snake.fill(spawn);
+        self.snake_direction = Direction::Right;
+
+        self.apples
+            .retain(|apple| !self.obstacles.contains(&apple.position) && apple.position != spawn);
+        self.pickups.retain(|pickup| {
+            !self.obstacles.contains(&pickup.position) && pickup.position != spawn
+        });
+        if self.mega_apple.as_ref().is_some_and(|mega_apple| {
+            mega_apple
+                .cells()
+                .any(|cell| self.obstacles.contains(&cell) || cell == spawn)
+        }) {
+            self.mega_apple = None;
+        }
+        self.spawn_apples();
+    }
+
+    /// Returns the walls of the loaded map and the chosen layout
+    fn layout_obstacles(&self) -> HashSet<(u16, u16)> {
+        let mut obstacles = self.settings.layout.obstacles(self.width, self.height);
+        if let Some(maze) = &self.settings.maze {
+            obstacles.extend(&maze.walls);
+        }
+        obstacles.extend(&self.random_walls);
+        obstacles.extend(&self.generated_walls);
+
+        obstacles
+    }
+
+    /// Scatters the configured number of walls, making sure every cell the snake could reach
+    /// before is still reachable and that the snake doesn't start right in front of one
+    fn place_random_walls(&mut self, spawn: (u16, u16)) {
+        const ATTEMPTS: usize = 100;
+
+        if self.settings.random_walls == 0 {
+            return;
+        }
+
+        let reachable = self.reachable_cells(spawn);
+        let ahead = self.head_target(spawn, self.snake_direction.get_vec2());
+        let candidates: Vec<_> = reachable
+            .iter()
+            .copied()
+            .filter(|&cell| !self.snake.contains(&cell) && Some(cell) != ahead)
+            .collect();
+
+        for _ in 0..ATTEMPTS {
+            let walls: HashSet<_> = candidates
+                .choose_multiple(&mut self.rng, self.settings.random_walls)
+                .copied()
+                .collect();
+            self.obstacles.extend(&walls);
+
+            // Walls only ever take cells away, so matching counts mean nothing got cut off
+            if self.reachable_cells(spawn).len() + walls.len() == reachable.len() {
+                self.random_walls = walls;
+                return;
+            }
+            self.obstacles.retain(|cell| !walls.contains(cell));
+        }
+    }
+
+    /// Flood fills the board from `start`, going through portals and around obstacles
+    fn reachable_cells(&self, start: (u16, u16)) -> HashSet<(u16, u16)> {
+        let mut reachable = HashSet::from([start]);
+        let mut frontier = vec![start];
+        while let Some(cell) = frontier.pop() {
+            for direction in Direction::ALL {
+                if let Some(next) = self
+                    .head_target(cell, direction.get_vec2())
+                    .filter(|next| !self.obstacles.contains(next))
+                    && reachable.insert(next)
+                {
+                    frontier.push(next);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// Finds the free cell closest to the middle of the board with room to move right from it
+    fn safe_spawn(&self) -> (u16, u16) {
+        let middle = (self.width / 2, self.height / 2);
+        let is_clear = |cell: (u16, u16)| {
+            !self.is_wall(cell)
+                && !self.is_portal(cell)
+                && !self.snake.contains(&cell)
+                && !self
+                    .rival
+                    .as_ref()
+                    .is_some_and(|rival| rival.body.contains(&cell))
+                && !self.apples.iter().any(|apple| apple.position == cell)
+        };
+
+        let height = self.height;
+        let mut candidates: Vec<_> = (0..self.width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .filter(|&(x, y)| {
+                x + Self::SPAWN_RUNWAY < self.width
+                    && (x..=x + Self::SPAWN_RUNWAY).all(|x| is_clear((x, y)))
+            })
+            .collect();
+        candidates.sort_by_key(|&(x, y)| x.abs_diff(middle.0) + y.abs_diff(middle.1));
+
+        candidates.first().copied().unwrap_or(middle)
+    }
+
+    /// Whether running into `cell` is a crash, which mines are just like walls for
+    fn is_wall(&self, cell: (u16, u16)) -> bool {
+        self.obstacles.contains(&cell)
+            || self.mines.contains(&cell)
+            || !self.in_arena(cell)
+            || self
+                .patrollers
+                .iter()
+                .any(|patroller| patroller.position == cell)
+    }
+
+    /// Moves every patroller a cell along. They turn around at anything that isn't free, but
+    /// wait for a snake to get out of the way
+    fn move_patrollers(&mut self) {
+        let free = self.free_cells();
+        let is_snake = |cell| {
+            self.snake.contains(&cell)
+                || self
+                    .rival
+                    .as_ref()
+                    .is_some_and(|rival| rival.body.contains(&cell))
+        };
+        // Each one moves in turn so they bump into where the others have just moved to
+        let mut patrollers = self.patrollers.clone();
+        for index in 0..patrollers.len() {
+            let others: Vec<_> = patrollers
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != index)
+                .map(|(_, patroller)| patroller.position)
+                .collect();
+            patrollers[index].step(
+                self.width,
+                self.height,
+                |cell| !free.contains(&cell) || others.contains(&cell),
+                is_snake,
+            );
+        }
+        self.patrollers = patrollers;
+    }
+
+    fn in_arena(&self, (x, y): (u16, u16)) -> bool {
+        (self.min_bound.0..=self.max_bound.0).contains(&x)
+            && (self.min_bound.1..=self.max_bound.1).contains(&y)
+    }
+
+    /// Opens the arena out by one ring, as far as the edge of the board
+    fn expand_arena(&mut self) {
+        self.min_bound = (
+            self.min_bound.0.saturating_sub(1),
+            self.min_bound.1.saturating_sub(1),
+        );
+        self.max_bound = (
+            (self.max_bound.0 + 1).min(self.width - 1),
+            (self.max_bound.1 + 1).min(self.height - 1),
+        );
+    }
+
+    /// Closes the arena in by one ring. Anything caught outside it is moved or destroyed, snakes
+    /// included
+    fn contract_arena(&mut self) {
+        let size = (
+            self.max_bound.0 - self.min_bound.0 + 1,
+            self.max_bound.1 - self.min_bound.1 + 1,
+        );
+        if size.0.min(size.1) <= Self::MIN_ARENA_SIZE {
+            return;
+        }
+        self.min_bound = (self.min_bound.0 + 1, self.min_bound.1 + 1);
+        self.max_bound = (self.max_bound.0 - 1, self.max_bound.1 - 1);
+
+        let (min, max) = (self.min_bound, self.max_bound);
+        let in_arena =
+            |(x, y): (u16, u16)| (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y);
+        self.pickups.retain(|pickup| in_arena(pickup.position));
+        self.apples.retain(|apple| in_arena(apple.position));
+        self.patrollers
+            .retain(|patroller| in_arena(patroller.position));
+        if self
+            .mega_apple
+            .as_ref()
+            .is_some_and(|mega_apple| !mega_apple.cells().all(in_arena))
+        {
+            self.mega_apple = None;
+        }
+        self.spawn_apples();
+
+        let player_caught = !self.god_mode() && !self.snake.iter().all(|&cell| self.in_arena(cell));
+        let rival_caught = self
+            .rival
+            .as_ref()
+            .is_some_and(|rival| !rival.body.iter().all(|&cell| self.in_arena(cell)));
+
+        match self.rival.as_mut() {
+            Some(rival) if rival.controller == Controller::Human => {
+                self.outcome = match (player_caught, rival_caught) {
+                    (false, false) => None,
+                    (false, true) => Some(Outcome::PlayerOneWins),
+                    (true, false) => Some(Outcome::PlayerTwoWins),
+                    (true, true) => Some(Outcome::Draw),
+                };
+                self.game_over = self.outcome.is_some();
+                return;
+            }
+            Some(rival) if rival.controller.is_teammate() => {
+                self.game_over = player_caught || rival_caught;
+                return;
+            }
+            Some(rival) if rival_caught => {
+                rival.body.clear();
+                rival.pending_growth = 0;
+                rival.respawn_ticks = Self::RIVAL_RESPAWN_TICKS;
+            }
+            _ => {}
+        }
+        if player_caught {
+            self.die();
+        }
+    }
+
+    /// Drops a mine on a free cell out of the way of the head, until there are too many
+    fn place_mine(&mut self) {
+        if self.mines.len() >= Self::MAX_MINES {
+            return;
+        }
+
+        let head = self.snake[0];
+        let cells: Vec<_> = self
+            .free_cells()
+            .into_iter()
+            .filter(|cell| cell.0.abs_diff(head.0) + cell.1.abs_diff(head.1) > 2)
+            .collect();
+        if let Some(&cell) = cells.choose(&mut self.rng) {
+            self.mines.insert(cell);
+        }
+    }
+
+    fn is_portal(&self, cell: (u16, u16)) -> bool {
+        self.portals.is_some_and(|(a, b)| cell == a || cell == b)
+    }
+
+    /// Returns every cell that isn't occupied by either snake, an obstacle, a portal, an apple, or
+    /// a pickup
+    fn free_cells(&self) -> Vec<(u16, u16)> {
+        let mut possible_positions = Vec::with_capacity(self.width as usize * self.height as usize);
+
+        for x in 0..self.width {
+            for y in 0..self.height {
+                if !self.snake.contains(&(x, y))
+                    && !self.is_wall((x, y))
+                    && !self.is_portal((x, y))
+                    && !self
+                        .rival
+                        .as_ref()
+                        .is_some_and(|rival| rival.body.contains(&(x, y)))
+                    && !self.apples.iter().any(|apple| apple.position == (x, y))
+                    && !self.pickups.iter().any(|pickup| pickup.position == (x, y))
+                    && !self
+                        .mega_apple
+                        .as_ref()
+                        .is_some_and(|mega_apple| mega_apple.contains((x, y)))
+                {
+                    possible_positions.push((x, y));
+                }
+            }
+        }
+
+        possible_positions
+    }
+
+    fn apple_count(&self) -> usize {
+        match self.mode() {
+            Mode::Tron => 0,
+            Mode::Frenzy => Self::FRENZY_APPLES,
+            Mode::Sequence => AppleColor::ORDER.len(),
+            // One for each player to go after
+            _ if self.has_teammate() => self.settings.apple_count.max(2),
+            _ => self.settings.apple_count,
+        }
+    }
+
+    /// Places apples on random free cells until there are `apple_count` of them or the board is full
+    fn spawn_apples(&mut self) {
+        if self.mode() == Mode::Sequence {
+            self.spawn_sequence_apples();
+            return;
+        }
+
+        let mut spawned = None;
+        // Dropped apples are extra, they don't stand in for the usual ones
+        while self.apples.iter().filter(|apple| !apple.dropped).count() < self.apple_count() {
+            let Some(position) = self.apple_cell() else {
+                break;
+            };
+            self.apple_spawns += 1;
+            if self.apple_spawns.is_multiple_of(Self::SPAWNS_PER_CHOICE)
+                && let Some(premium) = self.premium_apple_cell(position)
+            {
+                let choice = Some(self.apple_spawns);
+                self.apples.extend([
+                    Apple {
+                        choice,
+                        ..Apple::new(position)
+                    },
+                    Apple {
+                        choice,
+                        premium: true,
+                        ..Apple::new(premium)
+                    },
+                ]);
+                spawned = None;
+                continue;
+            }
+            spawned = Some(self.apples.len());
+            self.apples.push(Apple::new(position));
+        }
+
+        // Now and then an apple brings a second one along, if there's a free cell left for it
+        if let Some(index) = spawned
+            && self.rng.random_bool(Self::DOUBLE_APPLE_CHANCE)
+            && let Some(position) = self.apple_cell()
+        {
+            self.apple_pairs += 1;
+            self.apples[index].pair = Some(self.apple_pairs);
+            self.apples.push(Apple {
+                pair: Some(self.apple_pairs),
+                ..Apple::new(position)
+            });
+        }
+    }
+
+    /// Picks a free cell next to a wall or the edge of the board for the premium apple of a
+    /// choice, on the other side of the board from the normal one at `normal` if there's room
+    fn premium_apple_cell(&mut self, normal: (u16, u16)) -> Option<(u16, u16)> {
+        let (min, max) = (self.min_bound, self.max_bound);
+        let next_to_wall = |(x, y): (u16, u16)| {
+            x == min.0
+                || x == max.0
+                || y == min.1
+                || y == max.1
+                || Direction::ALL.into_iter().any(|direction| {
+                    self.step((x, y), direction.get_vec2())
+                        .is_some_and(|cell| self.is_wall(cell))
+                })
+        };
+        let cells: Vec<_> = self
+            .free_cells()
+            .into_iter()
+            .filter(|&cell| cell != normal && next_to_wall(cell))
+            .collect();
+
+        let middle = (self.width / 2, self.height / 2);
+        let opposite: Vec<_> = cells
+            .iter()
+            .copied()
+            .filter(|&(x, y)| {
+                (x < middle.0) != (normal.0 < middle.0) && (y < middle.1) != (normal.1 < middle.1)
+            })
+            .collect();
+        if opposite.is_empty() {
+            cells.choose(&mut self.rng).copied()
+        } else {
+            opposite.choose(&mut self.rng).copied()
+        }
+    }
+
+    /// Puts back any apples still to be eaten in this round of the sequence, spread out so the
+    /// order matters
+    fn spawn_sequence_apples(&mut self) {
+        for &color in &AppleColor::ORDER[self.next_color..] {
+            if self.apples.iter().any(|apple| apple.color == Some(color)) {
+                continue;
+            }
+
+            let spread_out: Vec<_> = away_from_head(&self.snake, self.free_cells())
+                .into_iter()
+                .filter(|&(x, y)| {
+                    self.apples.iter().all(|apple| {
+                        x.abs_diff(apple.position.0) + y.abs_diff(apple.position.1)
+                            >= Self::SEQUENCE_APPLE_SPACING
+                    })
+                })
+                .collect();
+            let position = match spread_out.choose(&mut self.rng) {
+                Some(&position) => Some(position),
+                None => self.apple_cell(),
+            };
+            let Some(position) = position else {
+                return;
+            };
+            self.apples.push(Apple {
+                color: Some(color),
+                ..Apple::new(position)
+            });
+        }
+    }
+
+    /// Picks a free cell for a new apple away from the head. Hardcore mode favours cells along
+    /// the walls, and corners most of all. Breadcrumb trails go a set distance from the head
+    /// when they can
+    fn apple_cell(&mut self) -> Option<(u16, u16)> {
+        if self.settings.breadcrumbs
+            && let Some(cell) = breadcrumb_cell(self.snake[0], &self.free_cells(), &mut self.rng)
+        {
+            return Some(cell);
+        }
+
+        let cells = away_from_head(&self.snake, self.free_cells());
+        if self.mode() != Mode::Hardcore {
+            return cells.choose(&mut self.rng).copied();
+        }
+
+        cells
+            .choose_weighted(&mut self.rng, |&(x, y)| {
+                let x_distance = x.min(self.width - 1 - x) as f64;
+                let y_distance = y.min(self.height - 1 - y) as f64;
+                1.0 / (x_distance + 1.0) + 1.0 / (y_distance + 1.0)
+            })
+            .ok()
+            .copied()
+    }
+
+    /// Ages every apple, moving the ones left uneaten for too long if that's enabled
+    fn update_apples(&mut self) {
+        // Pickups can take the last free cells, so the apple comes back once one is picked up
+        if self.apples.is_empty() && self.tutorial.is_none() {
+            self.spawn_apples();
+        }
+        for index in 0..self.apples.len() {
+            self.apples[index].age += 1;
+
+            if self.apples[index].age >= Self::APPLE_ROT_TICKS + Self::ROTTEN_APPLE_TICKS {
+                if let Some(position) = self.apple_cell() {
+                    self.apples[index].relocate(position);
+                }
+                continue;
+            }
+
+            if self.settings.relocate_apples && self.apples[index].age >= Self::APPLE_RELOCATE_TICKS
+            {
+                // The free cells exclude this apple, so it always lands somewhere new
+                if let Some(position) = self.apple_cell() {
+                    self.apples[index].relocate(position);
+                }
+            }
+        }
+    }
+
+    fn apple_state(&self, apple: &Apple) -> AppleState {
+        // Sequence apples stay fresh, eating one rotten would leave a gap in the order
+        if apple.age >= Self::APPLE_ROT_TICKS && self.mode() != Mode::Sequence {
+            AppleState::Rotten
+        } else {
+            AppleState::Fresh
+        }
+    }
+
+    /// Takes a point off every so often while the snake goes without an apple
+    fn decay_score(&mut self) {
+        self.decay_flash_ticks = self.decay_flash_ticks.saturating_sub(1);
+        // Tron's score counts ticks survived, so there's nothing to decay
+        if !self.settings.score_decay
+            || self.mode() == Mode::Tron
+            || self.score == 0
+            || !self
+                .ticks_since_apple
+                .is_multiple_of(Self::SCORE_DECAY_TICKS)
+        {
+            return;
+        }
+        self.score -= 1;
+        self.decay_flash_ticks = Self::DECAY_FLASH_TICKS;
+    }
+
+    /// Moves each apple a cell towards the head, unless something is in the way
+    fn drift_apples(&mut self) {
+        let head = self.snake[0];
+        for index in 0..self.apples.len() {
+            let cell = drift_toward(self.apples[index].position, head);
+            if self.free_cells().contains(&cell) {
+                self.apples[index].position = cell;
+            }
+        }
+    }
+
+    /// Hops the apple nearest the head one cell straight away from it if the head gets too close,
+    /// as long as that cell is free and the apple hasn't hopped too recently
+    fn evade(&mut self) {
+        self.apple_hop_cooldown = self.apple_hop_cooldown.saturating_sub(1);
+        if self.apple_hop_cooldown > 0 {
+            return;
+        }
+
+        let head = self.snake[0];
+        let distance = |(x, y): (u16, u16)| x.abs_diff(head.0) + y.abs_diff(head.1);
+        let Some(index) = (0..self.apples.len())
+            .filter(|&index| distance(self.apples[index].position) <= Self::EVADE_DISTANCE)
+            .min_by_key(|&index| distance(self.apples[index].position))
+        else {
+            return;
+        };
+
+        let (x, y) = self.apples[index].position;
+        let away = |from: u16, to: u16| (to as i16 - from as i16).signum();
+        let (dx, dy) = (away(head.0, x), away(head.1, y));
+        // Prefer hopping along the axis the head is further away on
+        let mut hops = [(dx, 0), (0, dy)];
+        if x.abs_diff(head.0) < y.abs_diff(head.1) {
+            hops.reverse();
+        }
+
+        let free = self.free_cells();
+        let hop = hops
+            .into_iter()
+            .filter(|&hop| hop != (0, 0))
+            .find_map(|(dx, dy)| {
+                let cell = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                (cell.0 < self.width && cell.1 < self.height && free.contains(&cell))
+                    .then_some(cell)
+            });
+        if let Some(cell) = hop {
+            self.apples[index].position = cell;
+            self.apple_hop_cooldown = Self::APPLE_HOP_COOLDOWN;
+        }
+    }
+
+    /// Counts down to the next earthquake, which moves every apple somewhere else
+    fn update_earthquake(&mut self) {
+        self.earthquake_ticks = self.earthquake_ticks.saturating_sub(1);
+        if self.earthquake_ticks > 0 {
+            return;
+        }
+
+        for index in 0..self.apples.len() {
+            if let Some(position) = self.apple_cell() {
+                self.apples[index].relocate(position);
+            }
+        }
+        self.earthquake_ticks = self.rng.random_range(Self::EARTHQUAKE_TICKS);
+    }
+
+    /// Counts down the mega apple's lifetime, and now and then places a new one where there's a
+    /// big enough gap
+    fn update_mega_apple(&mut self) {
+        if let Some(mega_apple) = &mut self.mega_apple {
+            mega_apple.ticks_left = mega_apple.ticks_left.saturating_sub(1);
+            if mega_apple.ticks_left == 0 {
+                self.mega_apple = None;
+            }
+            return;
+        }
+
+        if self.apple_count() == 0 || !self.rng.random_bool(Self::MEGA_APPLE_CHANCE) {
+            return;
+        }
+        let squares = apple::free_squares(&self.free_cells(), Self::MEGA_APPLE_SIZE);
+        if let Some(&anchor) = squares.choose(&mut self.rng) {
+            self.mega_apple = Some(MegaApple {
+                anchor,
+                size: Self::MEGA_APPLE_SIZE,
+                ticks_left: Self::MEGA_APPLE_TICKS,
+            });
+        }
+    }
+
+    /// Whether the apple should be hidden this frame to warn that it's about to move
+    fn is_apple_blinking(&self, apple: &Apple) -> bool {
+        self.settings.relocate_apples
+            && apple.age + Self::APPLE_BLINK_TICKS >= Self::APPLE_RELOCATE_TICKS
+            && apple.age % 2 == 1
+    }
+
+    /// Picks a free cell for a pickup, avoiding the cell right in front of the head so
+    /// harmful pickups can't appear somewhere the player has no way to dodge
+    fn pickup_cell(&mut self) -> Option<(u16, u16)> {
+        let ahead = self.step(self.snake[0], self.snake_direction.get_vec2());
+        let cells: Vec<_> = self
+            .free_cells()
+            .into_iter()
+            .filter(|&cell| Some(cell) != ahead)
+            .collect();
+
+        cells.choose(&mut self.rng).copied()
+    }
+
+    /// Places a new pickup of `kind` on the board if there is room for it
+    fn spawn_pickup(&mut self, kind: PickupKind) {
+        if let Some(position) = self.pickup_cell() {
+            self.pickups.push(Pickup {
+                position,
+                kind,
+                ticks_left: kind.lifetime(),
+            });
+        }
+    }
+
+    /// Counts down pickup lifetimes, relocating or removing expired ones, and rolls for new pickups
+    fn update_pickups(&mut self) {
+        for pickup in &mut self.pickups {
+            if !pickup.kind.persists() {
+                pickup.ticks_left = pickup.ticks_left.saturating_sub(1);
+            }
+        }
+        self.pickups
+            .retain(|pickup| pickup.ticks_left > 0 || pickup.kind.relocates());
+
+        for index in 0..self.pickups.len() {
+            if self.pickups[index].ticks_left == 0 {
+                match self.pickup_cell() {
+                    Some(position) => {
+                        self.pickups[index].position = position;
+                        self.pickups[index].ticks_left = self.pickups[index].kind.lifetime();
+                    }
+                    None => self.pickups[index].ticks_left = 1,
+                }
+            }
+        }
+
+        for index in 0..self.pickup_chances.len() {
+            let (kind, chance) = self.pickup_chances[index];
+            if !self.pickups.iter().any(|pickup| pickup.kind == kind)
+                && self.can_spawn(kind)
+                && self.rng.random_bool(chance)
+            {
+                self.spawn_pickup(kind);
+            }
+        }
+    }
+
+    /// Whether the current state of the game calls for a pickup of `kind`
+    fn can_spawn(&self, kind: PickupKind) -> bool {
+        match kind {
+            _ if self.mode() == Mode::Tron => false,
+            PickupKind::SlowDown => {
+                self.snake_move_time <= self.min_move_time() + Self::SLOW_SPAWN_MARGIN
+            }
+            PickupKind::Shrink => self.snake.len() > Self::SHRINK_SPAWN_LENGTH,
+            _ => true,
+        }
+    }
+
+    /// The time between moves, taking active effects into account
+    fn move_interval(&self) -> Duration {
+        // A time freeze overrides every other effect on the speed
+        if self.freeze_ticks > 0 {
+            return Duration::from_millis(Self::FREEZE_MOVE_TIME);
+        }
+
+        let mut move_time = self.snake_move_time;
+        if self.boost_ticks > 0 {
+            move_time = move_time * (100 - Self::BOOST_PERCENT) / 100;
+        }
+        if self.slow_ticks > 0 {
+            move_time += Self::SLOW_MS;
+        }
+        if self.sprinting {
+            move_time /= 2;
+        }
+        if self.brake_ticks > 0 {
+            move_time = move_time * 3 / 2;
+        }
+
+        // Halving a floor of a millisecond or two would otherwise leave no time at all
+        Duration::from_millis(move_time.max(1))
+    }
+
+    /// Zen mode always wraps, whatever the settings say
+    fn border_mode(&self) -> BorderMode {
+        match self.mode() {
+            Mode::Zen => BorderMode::Wrap,
+            Mode::Nokia => BorderMode::Solid,
+            _ => self.settings.border_mode,
+        }
+    }
+
+    /// Returns the cell one step away in `direction`, or `None` if it's off the board. A board
+    /// that's shrunk or still growing has its edge where the arena ends
+    fn step(&self, position: (u16, u16), direction: (i16, i16)) -> Option<(u16, u16)> {
+        let x = position.0 as i16 + direction.0;
+        let y = position.1 as i16 + direction.1;
+
+        match self.border_mode() {
+            BorderMode::Solid | BorderMode::Bounce => {
+                (x >= 0 && y >= 0 && self.in_arena((x as u16, y as u16)))
+                    .then_some((x as u16, y as u16))
+            }
+            BorderMode::Wrap => Some(self.wrap_into_arena(x, y)),
+        }
+    }
+
+    /// Brings a cell that's gone off one edge of the arena back in on the opposite one
+    fn wrap_into_arena(&self, x: i16, y: i16) -> (u16, u16) {
+        let (min, max) = (self.min_bound, self.max_bound);
+        let wrap = |value: i16, min: u16, max: u16| {
+            let span = (max - min + 1) as i16;
+            ((value - min as i16).rem_euclid(span) + min as i16) as u16
+        };
+
+        (wrap(x, min.0, max.0), wrap(y, min.1, max.1))
+    }
+
+    /// Returns the cell `head` moves to in `direction`, coming out of the linked portal if it
+    /// steps into one, or `None` if that's off the board
+    fn head_target(&self, head: (u16, u16), direction: (i16, i16)) -> Option<(u16, u16)> {
+        let cell = self.step(head, direction)?;
+
+        Some(match self.portals {
+            Some((a, b)) if cell == a => b,
+            Some((a, b)) if cell == b => a,
+            _ => cell,
+        })
+    }
+
+    /// Steers the snake in `direction` after any mirroring, or the opposite one while the
+    /// controls are reversed
+    fn turn(&mut self, direction: Direction) {
+        if self
+            .rival
+            .as_ref()
+            .is_some_and(|rival| rival.controller == Controller::Twin)
+        {
+            self.turn_rival(Mirror::LeftRight.apply(direction));
+        }
+
+        let direction = match self.settings.mirror {
+            Some(mirror) => mirror.apply(direction),
+            None => direction,
+        };
+        let direction = if self.reversed_ticks > 0 {
+            direction.opposite()
+        } else {
+            direction
+        };
+
+        if self.is_valid_turn(direction) {
+            self.snake_direction = direction;
+        }
+    }
+
+    /// Steers towards a cell of the board clicked or dragged over, along whichever axis it's
+    /// further away from the head on
+    fn click(&mut self, mouse: MouseEvent, area: Rect) {
+        if !matches!(
+            mouse.kind,
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
+        ) || self.game_over
+            || self.demo
+            || self.choosing_mode
+            || self.choosing_level
+            || self.paused_at.is_some()
+            || self.help_opened.is_some()
+        {
+            return;
+        }
+
+        let board_rect = self.board_rect(area);
+        let (Some(column), Some(row)) = (
+            mouse.column.checked_sub(board_rect.x),
+            mouse.row.checked_sub(board_rect.y),
+        ) else {
+            return;
+        };
+        let (x, y) = (column / 2, row);
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let (head_x, head_y) = self.snake[0];
+        let (dx, dy) = (x as i16 - head_x as i16, y as i16 - head_y as i16);
+        let direction = match (dx.abs() > dy.abs(), dx > 0, dy > 0) {
+            _ if dx == 0 && dy == 0 => return,
+            (true, true, _) => Direction::Right,
+            (true, false, _) => Direction::Left,
+            (false, _, true) => Direction::Down,
+            (false, _, false) => Direction::Up,
+        };
+        self.turn(direction);
+    }
+
+    fn is_valid_turn(&self, direction: Direction) -> bool {
+        self.can_turn(&self.snake, self.snake_direction, direction)
+    }
+
+    /// Whether a snake with `body` moving in `current` can turn to `direction` without
+    /// reversing into itself
+    fn can_turn(&self, body: &[(u16, u16)], current: Direction, direction: Direction) -> bool {
+        if body.len() > 1 {
+            self.head_target(body[0], direction.get_vec2()) != Some(body[1])
+        } else {
+            current.opposite() != direction
+        }
+    }
+
+    fn has_second_player(&self) -> bool {
+        self.rival.as_ref().is_some_and(|rival| {
+            matches!(rival.controller, Controller::Human | Controller::Partner)
+        })
+    }
+
+    fn has_teammate(&self) -> bool {
+        self.rival
+            .as_ref()
+            .is_some_and(|rival| rival.controller.is_teammate())
+    }
+
+    /// Whether the whole snake shows in invisible mode, which it does for the tick after eating
+    /// an apple
+    fn body_revealed(&self) -> bool {
+        self.apples_eaten > 0 && self.ticks_since_apple <= 1
+    }
+
+    fn god_mode(&self) -> bool {
+        self.debug.as_ref().is_some_and(|debug| debug.god_mode)
+    }
+
+    /// Handles a cheat key in a debug game, returning whether `code` was one. Keys bound in the
+    /// keymap always go to the game instead
+    fn debug_key(&mut self, code: KeyCode) -> bool {
+        if self.settings.keymap.action(code).is_some() {
+            return false;
+        }
+        let Some(debug) = &mut self.debug else {
+            return false;
+        };
+
+        // Shifted, so they don't get in the way of steering with HJKL
+        let cursor_direction = match code {
+            KeyCode::Char('I') => Some(Direction::Up),
+            KeyCode::Char('K') => Some(Direction::Down),
+            KeyCode::Char('J') => Some(Direction::Left),
+            KeyCode::Char('L') => Some(Direction::Right),
+            _ => None,
+        };
+        if let Some(direction) = cursor_direction {
+            debug.move_cursor(direction, self.width, self.height);
+            return true;
+        }
+
+        match code {
+            KeyCode::Char('x') => {
+                debug.god_mode = !debug.god_mode;
+                let state = if debug.god_mode { "on" } else { "off" };
+                self.show_message(format!("God mode {state}"));
+            }
+            KeyCode::Char('o') => {
+                let cursor = debug.cursor;
+                if self.free_cells().contains(&cursor) {
+                    // Takes the place of the first apple rather than piling more on
+                    match self.apples.first_mut() {
+                        Some(apple) => apple.relocate(cursor),
+                        None => self.apples.push(Apple::new(cursor)),
+                    }
+                }
+            }
+            KeyCode::Char('+') => {
+                let tail = self.snake[self.snake.len() - 1];
+                self.snake.push(tail);
+            }
+            KeyCode::Char('-') if self.snake.len() > 1 => {
+                self.snake.pop();
+            }
+            KeyCode::Char('f') => {
+                let message = match storage::write(debug::DUMP_FILE_NAME, &format!("{self:#?}\n")) {
+                    Ok(()) => format!("Dumped to {}", debug::DUMP_FILE_NAME),
+                    Err(_) => "Couldn't dump the game".to_string(),
+                };
+                self.show_message(message);
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn turn_rival(&mut self, direction: Direction) {
+        if let Some(rival) = &self.rival
+            && self.can_turn(&rival.body, rival.direction, direction)
+            && let Some(rival) = &mut self.rival
+        {
+            rival.direction = direction;
+        }
+    }
+}
+
+impl Game {
+    fn title(&self) -> String {
+        let mut mode = String::new();
+        if self.demo {
+            mode.push_str(" DEMO  Press any key ");
+        }
+        // Always on show, so a debug game can't be passed off as a real one
+        if let Some(debug) = &self.debug {
+            mode.push_str(" DEBUG ");
+            if debug.god_mode {
+                mode.push_str(" GOD ");
+            }
+        }
+        if let Some(generator) = self.settings.generator {
+            mode.push_str(&format!(" {} ", generator.name().to_uppercase()));
+        }
+        if self.tutorial.is_some() {
+            mode.push_str(" TUTORIAL ");
+        }
+        if self.mode() == Mode::Evasive {
+            mode.push_str(" EVASIVE ");
+        }
+        if self.mode() == Mode::Invisible {
+            mode.push_str(" INVISIBLE ");
+        }
+        if self.mode() == Mode::Nokia
+            && let Some(level) = self.settings.speed_level
+        {
+            mode.push_str(&format!(" NOKIA  Speed: {level} "));
+        }
+        if self.mode() == Mode::Sequence {
+            mode.push_str(&format!(
+                " Next: {} ",
+                AppleColor::ORDER[self.next_color].name()
+            ));
+        }
+        if self.mode() == Mode::Practice {
+            mode.push_str(&format!(" PRACTICE  Rewinds: {} ", self.history.len()));
+        }
+        if self.settings.fog {
+            mode.push_str(" FOG ");
+        }
+        if self.settings.mirror.is_some() {
+            mode.push_str(" MIRROR ");
+        }
+        if self.settings.difficulty != Difficulty::Normal {
+            mode.push_str(&format!(
+                " {} ",
+                self.settings.difficulty.name().to_uppercase()
+            ));
+        }
+        if let Some(date) = self.daily {
+            mode.push_str(&format!(" Daily {date} "));
+        }
+        if let Some(code) = &self.settings.seed {
+            mode.push_str(&format!(" Seed: {code} "));
+        }
+        if self.mode() == Mode::Hardcore {
+            mode.push_str(" HARDCORE ");
+        }
+        if self.border_mode() == BorderMode::Bounce {
+            mode.push_str(" BOUNCE ");
+        }
+
+        if let Some(rival) = &self.rival {
+            return match rival.controller {
+                Controller::Human => format!("{mode} P1: {}  P2: {} ", self.score, rival.score),
+                Controller::Partner | Controller::Twin => format!(
+                    "{mode} Score: {}  Length: {} ",
+                    self.score,
+                    self.snake.len() + rival.body.len()
+                ),
+                Controller::Computer => {
+                    format!("{mode} Score: {}  AI: {} ", self.score, rival.score)
+                }
+            };
+        }
+
+        if self.settings.lives > 1 {
+            mode.push_str(&format!(
+                " Lives: {} ",
+                self.glyphs.life.repeat(self.lives as usize)
+            ));
+        }
+
+        let mut title = if self.mode() == Mode::Tron {
+            format!("{mode} Ticks: {} ", self.score)
+        } else if self.mode() == Mode::Frenzy {
+            format!("{mode} Apples: {} ", self.apples_eaten)
+        } else if self.settings.levels {
+            format!(
+                "{mode} Level: {}  Score: {}  Best: {} ",
+                self.level,
+                self.score,
+                self.best_score.max(self.score)
+            )
+        } else {
+            format!(
+                "{mode} Score: {}  Best: {} ",
+                self.score,
+                self.best_score.max(self.score)
+            )
+        };
+        if self.sprinting {
+            title.push_str(&format!(" {} ", self.glyphs.sprint));
+        }
+        if self.settings.phases {
+            title.push_str(&format!(" Phases: {} ", self.phases));
+        }
+        if self.brake_ticks > 0 {
+            title.push_str(" BRAKING ");
+        } else if self.brake_cooldown > 0 {
+            title.push_str(&format!(" Brake: {} ", self.brake_cooldown));
+        }
+        if self.settings.speed_scoring || self.settings.length_scoring {
+            title.push_str(&format!(" (+{}) ", self.apple_value()));
+        }
+        if let Some(time_left) = self.time_left() {
+            let seconds = time_left.as_secs_f64().ceil() as u64;
+            title.push_str(&format!(" Time: {} ", format_clock(seconds)));
+        }
+
+        if let Some(golden) = self
+            .pickups
+            .iter()
+            .find(|pickup| pickup.kind == PickupKind::Golden)
+        {
+            title.push_str(&format!(" Golden: {} ", golden.ticks_left));
+        }
+
+        if self.freeze_ticks > 0 {
+            title.push_str(&format!(" {} {} ", self.glyphs.freeze, self.freeze_ticks));
+        }
+        if self.boost_ticks > 0 {
+            title.push_str(" BOOST ");
+        }
+        if self.slow_ticks > 0 {
+            title.push_str(" SLOW ");
+        }
+        if self.ghost_ticks > 0 {
+            title.push_str(&format!(" GHOST: {} ", self.ghost_ticks));
+        }
+        if self.reversed_ticks > 0 {
+            title.push_str(" REVERSED ");
+        }
+        if self.magnet_ticks > 0 {
+            title.push_str(&format!(" MAGNET: {} ", self.magnet_ticks));
+        }
+        if self.combo > 1 {
+            title.push_str(&format!(" x{} ", self.combo));
+        }
+
+        title
+    }
+
+    /// Whether `cell` is close enough to the head to be seen through the fog
+    fn is_visible(&self, cell: (u16, u16)) -> bool {
+        let head = self.snake[0];
+        cell.0.abs_diff(head.0).max(cell.1.abs_diff(head.1)) <= Self::FOG_RADIUS
+    }
+
+    /// Draws the seconds left on the countdown in big digits over the middle of the board
+    fn render_countdown(&self, started: Instant, board_rect: Rect, buf: &mut Buffer) {
+        let seconds_left = Self::COUNTDOWN_SECONDS
+            .saturating_sub(started.elapsed().as_secs())
+            .clamp(1, Self::COUNTDOWN_SECONDS);
+        let Some(digit) = BIG_DIGITS.get(seconds_left as usize - 1) else {
+            return;
+        };
+
+        let (width, height) = (digit[0].chars().count() as u16, digit.len() as u16);
+        let left = (self.width.saturating_sub(width)) / 2;
+        let top = (self.height.saturating_sub(height)) / 2;
+        for (y, row) in digit.iter().enumerate() {
+            for (x, pixel) in row.chars().enumerate() {
+                if pixel != ' ' {
+                    let (x, y) = (left + x as u16, top + y as u16);
+                    buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                        .set_symbol(self.glyphs.countdown)
+                        .set_fg(Color::Yellow);
+                }
+            }
+        }
+    }
+
+    /// Shades every other cell of the arena, both characters of each
+    fn render_grid(&self, board_rect: Rect, buf: &mut Buffer) {
+        for x in 0..self.width {
+            for y in 0..self.height {
+                if (x + y).is_multiple_of(2) && self.in_arena((x, y)) {
+                    let (column, row) = ((x * 2) + board_rect.x, y + board_rect.y);
+                    buf[(column, row)].set_bg(Game::GRID_COLOR);
+                    buf[(column + 1, row)].set_bg(Game::GRID_COLOR);
+                }
+            }
+        }
+    }
+
+    fn render_help(&self, border_rect: Rect, buf: &mut Buffer) {
+        let keys = |action| self.settings.keymap.key_names(action, self.glyphs);
+        let mut controls = vec![
+            (keys(Action::Up), "Up"),
+            (keys(Action::Down), "Down"),
+            (keys(Action::Left), "Left"),
+            (keys(Action::Right), "Right"),
+        ];
+        if self.has_second_player() {
+            let keys: Vec<_> = Self::SECOND_PLAYER_KEYS
+                .iter()
+                .map(|(key, _)| key.to_string())
+                .collect();
+            controls.push((keys.join("/"), "Player two"));
+        }
+        controls.extend([
+            (keys(Action::Sprint), "Sprint"),
+            (keys(Action::Brake), "Brake"),
+            (keys(Action::Pause), "Pause"),
+            ("u".to_string(), "Rewind (practice)"),
+            ("t".to_string(), "Achievements"),
+            ("g".to_string(), "Grid"),
+            (keys(Action::Restart), "Restart"),
+            ("m".to_string(), "Menu"),
+            (keys(Action::Quit), "Quit"),
+        ]);
+        let lines: Vec<Line> = controls
+            .iter()
+            .map(|(key, action)| Line::from(format!("{key:<12}{action:<17}")))
+            .chain([
+                Line::from(""),
+                Line::from("Press any key to close").centered(),
+            ])
+            .collect();
+
+        let width = (lines[0].width() as u16 + 2).min(border_rect.width);
+        let height = (lines.len() as u16 + 2).min(border_rect.height);
+        let help_rect = Rect::new(
+            border_rect.x + (border_rect.width - width) / 2,
+            border_rect.y + (border_rect.height - height) / 2,
+            width,
+            height,
+        );
+
+        buf.set_style(border_rect, Style::new().add_modifier(Modifier::DIM));
+        Clear.render(help_rect, buf);
+        let block = Block::bordered().title(Line::from(" Controls ").centered());
+        let inner = block.inner(help_rect);
+        block.render(help_rect, buf);
+        Text::from(lines).render(inner, buf);
+    }
+
+    /// Hides everything far from the head, pointing out hidden apples with arrows on the nearest
+    /// edge of the border
+    fn render_fog(&self, board_rect: Rect, buf: &mut Buffer) {
+        for x in 0..self.width {
+            for y in 0..self.height {
+                if !self.is_visible((x, y)) {
+                    buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                        .set_symbol("  ")
+                        .set_style(Style::reset());
+                }
+            }
+        }
+
+        let hint = Style::new().fg(Color::Red).add_modifier(Modifier::DIM);
+        for apple in &self.apples {
+            let (x, y) = apple.position;
+            if self.is_visible((x, y)) {
+                continue;
+            }
+
+            let edges = [
+                (x, Direction::Left, (board_rect.x - 1, board_rect.y + y)),
+                (
+                    self.width - 1 - x,
+                    Direction::Right,
+                    (board_rect.x + self.width * 2, board_rect.y + y),
+                ),
+                (y, Direction::Up, (board_rect.x + x * 2, board_rect.y - 1)),
+                (
+                    self.height - 1 - y,
+                    Direction::Down,
+                    (board_rect.x + x * 2, board_rect.y + self.height),
+                ),
+            ];
+            if let Some((_, direction, position)) =
+                edges.into_iter().min_by_key(|(distance, ..)| *distance)
+            {
+                buf[position]
+                    .set_symbol(self.glyphs.arrow(direction))
+                    .set_style(hint);
+            }
+        }
+    }
+
+    /// The keys worth knowing about in the state the game is in, and what they do. The menus
+    /// have their own
+    fn hints(&self) -> Vec<(String, &'static str)> {
+        let key = |action| self.settings.keymap.key_name(action, self.glyphs);
+        if self.choosing_mode || self.choosing_level || self.demo || self.dying.is_some() {
+            Vec::new()
+        } else if self.game_over {
+            vec![
+                (key(Action::Restart), "restart"),
+                (key(Action::Quit), "quit"),
+            ]
+        } else if self.paused_at.is_some() {
+            vec![
+                (key(Action::Pause), "resume"),
+                (key(Action::Restart), "restart"),
+                (key(Action::Quit), "quit"),
+            ]
+        } else if self.can_pause() {
+            vec![
+                (key(Action::Pause), "pause"),
+                (key(Action::Quit), "quit"),
+                ("?".to_string(), "help"),
+            ]
+        } else {
+            vec![(key(Action::Quit), "quit"), ("?".to_string(), "help")]
+        }
+    }
+
+    /// Where the board is drawn in `area`, roughly 1x2 terminal character size
+    fn board_rect(&self, area: Rect) -> Rect {
+        Rect::new(
+            area.x + ((area.width / 2).saturating_sub(self.width)),
+            area.y + ((area.height / 2).saturating_sub(self.height / 2)),
+            (self.width - 1) * 2,
+            self.height,
+        )
+    }
+
+    /// How many segments of the snake are drawn, counting from the head, which is all of them
+    /// unless it's dying
+    fn shown_segments(&self) -> usize {
+        let Some(crashed) = self.dying else {
+            return self.snake.len();
+        };
+        let elapsed = crashed.elapsed();
+        if elapsed < Self::DEATH_FLASH_TIME {
+            return if (elapsed.as_millis() / 100).is_multiple_of(2) {
+                self.snake.len()
+            } else {
+                0
+            };
+        }
+
+        let vanished = (elapsed - Self::DEATH_FLASH_TIME).as_secs_f64()
+            / (Self::DEATH_TIME - Self::DEATH_FLASH_TIME).as_secs_f64();
+        self.snake.len() - ((self.snake.len() as f64 * vanished) as usize).min(self.snake.len())
+    }
+
+    /// The cell the snake crashed into, or its head if that's up against the border
+    fn crash_cell(&self) -> (u16, u16) {
+        self.step(self.snake[0], self.snake_direction.get_vec2())
+            .filter(|&cell| self.is_wall(cell) || self.snake.contains(&cell))
+            .unwrap_or(self.snake[0])
+    }
+
+    fn title_style(&self) -> Style {
+        // Flashes when the score decays
+        if self.decay_flash_ticks > 0 {
+            Style::new().fg(Color::LightRed)
+        } else if self.eat_flash_ticks > 0 {
+            Style::new().fg(Color::Yellow)
+        } else {
+            Style::new()
+        }
+    }
+
+    fn border_style(&self) -> Style {
+        if self.phase_flash_ticks > 0 {
+            Style::new().fg(Color::LightMagenta)
+        } else if self.eat_flash_ticks == Self::EAT_FLASH_TICKS {
+            // Only pulses for the tick the apple was eaten on
+            Style::new().fg(Color::Yellow)
+        } else if self.reversed_ticks > 0 {
+            Style::new().fg(Color::Red)
+        } else if self.settings.earthquakes
+            && self.earthquake_ticks <= Self::EARTHQUAKE_WARNING_TICKS
+        {
+            // Warns that the apples are about to move
+            Style::new().fg(Color::Yellow)
+        } else if self.mode() == Mode::Zen {
+            Style::new().fg(Color::Green)
+        } else {
+            Style::new()
+        }
+    }
+}
+
+impl Widget for &Game {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let quit = self.settings.keymap.key_name(Action::Quit, self.glyphs);
+        let (min_width, min_height) = self.min_terminal_size();
+        if area.width < min_width || area.height < min_height {
+            let message = Text::from(format!(
+                "Terminal too small\nNeed {min_width}x{min_height}, have {}x{}\nResize it, or press {quit} to quit",
+                area.width, area.height
+            ))
+            .centered();
+            let top = area.y + (area.height / 2).saturating_sub(1);
+            let message_rect = Rect::new(area.x, top, area.width, 3.min(area.bottom() - top));
+            message.render(message_rect, buf);
+            return;
+        }
+
+        let board_rect = self.board_rect(area);
+
+        let border_rect = Rect::new(
+            board_rect.x - 1,
+            board_rect.y - 1,
+            board_rect.width + 4,
+            board_rect.height + 2,
+        );
+
+        let title = Line::from(self.title())
+            .centered()
+            .style(self.title_style());
+        let border = Block::bordered()
+            .border_type(ratatui::widgets::BorderType::Plain)
+            .border_set(match self.border_mode() {
+                BorderMode::Solid => self.glyphs.solid_border,
+                BorderMode::Bounce => self.glyphs.bounce_border,
+                BorderMode::Wrap => self.glyphs.wrap_border,
+            })
+            .border_style(self.border_style());
+
+        // A growing board is only as big as it's grown so far while it's being played on, but the
+        // menus and the title still get all the room it'll ever need
+        let growing_rect = (self.settings.growing
+            && self.entering_seed.is_none()
+            && !self.choosing_mode
+            && !self.choosing_level
+            && !self.game_over)
+            .then(|| {
+                let (min, max) = (self.min_bound, self.max_bound);
+                Rect::new(
+                    board_rect.x + min.0 * 2 - 1,
+                    board_rect.y + min.1 - 1,
+                    (max.0 - min.0 + 1) * 2 + 2,
+                    max.1 - min.1 + 3,
+                )
+            });
+        match growing_rect {
+            Some(arena_rect) => {
+                border.clone().render(arena_rect, buf);
+                title.render(
+                    Rect::new(border_rect.x, border_rect.y, border_rect.width, 1),
+                    buf,
+                );
+            }
+            None => border.clone().title(title).render(border_rect, buf),
+        }
+
+        if let Some(typed) = &self.entering_seed {
+            let lines = vec![
+                Line::from("Enter a seed"),
+                Line::from(""),
+                Line::from(format!("> {typed}_")),
+                Line::from(""),
+                Line::from("Enter to confirm, Esc to cancel"),
+            ];
+
+            let mut text_rect = border_rect;
+            text_rect.y += 4;
+            text_rect.height = text_rect.height.saturating_sub(4);
+            Text::from(lines).centered().render(text_rect, buf);
+        } else if let Some(selected) = self.settings_row {
+            let mut lines = vec![Line::from("Settings"), Line::from("")];
+            for (index, row) in SettingsRow::ALL.into_iter().enumerate() {
+                let style = if index == selected {
+                    Style::new().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+                lines.push(
+                    Line::from(format!("{}: {}", row.label(), row.value(&self.settings)))
+                        .style(style),
+                );
+            }
+            lines.extend([
+                Line::from(""),
+                Line::from(format!(
+                    "{}{} to pick, {}{} to change",
+                    self.glyphs.arrow(Direction::Up),
+                    self.glyphs.arrow(Direction::Down),
+                    self.glyphs.arrow(Direction::Left),
+                    self.glyphs.arrow(Direction::Right)
+                )),
+                Line::from("Enter to go back"),
+            ]);
+
+            let mut text_rect = border_rect;
+            text_rect.y += 2;
+            text_rect.height = text_rect.height.saturating_sub(2);
+            Text::from(lines).centered().render(text_rect, buf);
+        } else if self.choosing_mode {
+            let mut lines = vec![Line::from(format!(
+                "SNAKE  {}{} Enter  Tab: settings",
+                self.glyphs.arrow(Direction::Up),
+                self.glyphs.arrow(Direction::Down)
+            ))];
+            for (index, mode) in Mode::ALL.into_iter().enumerate() {
+                let style = if index == self.menu_selection {
+                    Style::new().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+                lines.push(
+                    Line::from(format!("{}  {}", mode.key(), mode.description())).style(style),
+                );
+            }
+            lines.extend([
+                Line::from(""),
+                Line::from(format!(
+                    "Difficulty: {} (e/n/h to change)",
+                    self.settings.difficulty.name()
+                )),
+                Line::from(match &self.settings.seed {
+                    Some(code) => format!("Seed: {code} (g)  {quit} to quit"),
+                    None => format!("g to enter a seed, {quit} to quit"),
+                }),
+            ]);
+
+            // Just inside the border, there are a lot of modes to fit in
+            let mut text_rect = border_rect;
+            text_rect.y += 1;
+            text_rect.height = text_rect.height.saturating_sub(2);
+            Text::from(lines).centered().render(text_rect, buf);
+        } else if self.choosing_level {
+            let lines = vec![
+                Line::from("Choose a speed"),
+                Line::from(""),
+                Line::from(format!(
+                    "1 (slowest) to {} (fastest)",
+                    Settings::MAX_SPEED_LEVEL
+                )),
+                Line::from(""),
+                Line::from(format!("Press {quit} to quit")),
+            ];
+
+            let mut text_rect = border_rect;
+            text_rect.y += 4;
+            text_rect.height = text_rect.height.saturating_sub(4);
+            Text::from(lines).centered().render(text_rect, buf);
+        } else if self.game_over && self.dying.is_none() {
+            let result = if self.won {
+                "YOU WIN"
+            } else if self.time_up {
+                "Time's up!"
+            } else {
+                self.outcome
+                    .map_or("Game Over", |outcome| outcome.message())
+            };
+            let best_combo = if self.settings.combos {
+                format!("\nBest combo: x{}", self.best_combo)
+            } else {
+                String::new()
+            };
+            let final_score = if self.mode() == Mode::Frenzy {
+                format!("\nApples eaten: {}", self.apples_eaten)
+            } else if self.time_left().is_some() || self.won {
+                format!("\nFinal score: {}", self.score)
+            } else if self.mode() == Mode::Tron {
+                let filled = self.snake.len() * 100 / self.open_cells();
+                format!(
+                    "\nSurvived {} ticks\nFilled {filled}% of the board",
+                    self.score
+                )
+            } else {
+                String::new()
+            };
+            let daily_best = match (self.daily, self.daily_best) {
+                (None, _) => String::new(),
+                (Some(_), Some(best)) if best >= self.score => format!("\nToday's best: {best}"),
+                (Some(_), _) if self.mode() == Mode::Practice => String::new(),
+                (Some(_), _) => "\nNew best today!".to_string(),
+            };
+            // Neither of these are saved
+            let best = if self.mode() == Mode::Practice || self.debug.is_some() {
+                String::new()
+            } else if self.score > self.best_score {
+                "\nNew best!".to_string()
+            } else {
+                format!("\nBest: {}", self.best_score)
+            };
+            let play_time = format!("\nTime: {}", format_clock(self.play_time.as_secs()));
+            let seed = match &self.settings.seed {
+                Some(code) => format!("\nSeed: {code}"),
+                None => String::new(),
+            };
+            let rewind = if self.mode() == Mode::Practice && self.history.len() > 0 {
+                "\nPress u to rewind"
+            } else {
+                ""
+            };
+            Text::from(format!(
+                "\n\n\n\n{result}{final_score}{best}{best_combo}{daily_best}{play_time}{seed}\n\nPress {} to restart{rewind}\nPress m for the menu\nPress {quit} to quit",
+                self.settings.keymap.key_name(Action::Restart, self.glyphs)
+            ))
+            .centered()
+            .render(border_rect, buf);
+        } else {
+            if self.settings.grid {
+                self.render_grid(board_rect, buf);
+            }
+
+            for (x, y) in &self.obstacles {
+                buf[((x * 2) + board_rect.x, y + board_rect.y)].set_symbol(self.glyphs.wall);
+            }
+
+            for patroller in &self.patrollers {
+                let (x, y) = patroller.position;
+                buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                    .set_symbol(self.glyphs.wall)
+                    .set_fg(Color::LightRed);
+            }
+
+            for x in 0..self.width {
+                for y in 0..self.height {
+                    if !self.in_arena((x, y)) && growing_rect.is_none() {
+                        buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                            .set_symbol(self.glyphs.outside)
+                            .set_fg(Color::DarkGray);
+                    }
+                }
+            }
+
+            for (x, y) in &self.mines {
+                buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                    .set_symbol(self.glyphs.mine)
+                    .set_fg(Color::Red);
+            }
+
+            if let Some((a, b)) = self.portals {
+                for (x, y) in [a, b] {
+                    buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                        .set_symbol(self.glyphs.portal)
+                        .set_fg(Color::LightMagenta);
+                }
+            }
+
+            for pickup in &self.pickups {
+                let (x, y) = pickup.position;
+                buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                    .set_symbol(self.glyphs.pickup(pickup.kind))
+                    .set_fg(pickup.kind.color());
+            }
+
+            if let Some(mega_apple) = &self.mega_apple {
+                for (x, y) in mega_apple.cells() {
+                    buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                        .set_symbol(self.glyphs.apple)
+                        .set_fg(Color::Red);
+                }
+            }
+
+            for apple in &self.apples {
+                if self.is_apple_blinking(apple) {
+                    continue;
+                }
+                let (x, y) = apple.position;
+                let cell = &mut buf[((x * 2) + board_rect.x, y + board_rect.y)];
+                if let Some(color) = apple.color {
+                    cell.set_symbol(self.glyphs.apple).set_fg(color.color());
+                    continue;
+                }
+                if apple.premium && self.apple_state(apple) == AppleState::Fresh {
+                    cell.set_symbol(self.glyphs.apple)
+                        .set_fg(Color::LightCyan)
+                        .set_style(Modifier::BOLD);
+                    continue;
+                }
+                match self.apple_state(apple) {
+                    AppleState::Fresh if self.magnet_ticks > 0 => cell
+                        .set_symbol(self.glyphs.apple)
+                        .set_fg(Color::LightYellow),
+                    AppleState::Fresh => cell.set_symbol(self.glyphs.apple).set_fg(Color::Red),
+                    AppleState::Rotten => cell
+                        .set_symbol(self.glyphs.rotten_apple)
+                        .set_fg(Color::DarkGray),
+                };
+            }
+
+            // A ghost snake is drawn see-through so it's clear overlapping is safe
+            let body_symbol = if self.ghost_ticks > 0 {
+                self.glyphs.ghost_body
+            } else if self.mode() == Mode::Nokia {
+                self.glyphs.nokia_body
+            } else {
+                self.glyphs.body
+            };
+            let hides_body = self.mode() == Mode::Invisible && !self.body_revealed();
+            let shown_segments = self.shown_segments();
+            for (index, (x, y)) in self.snake.iter().enumerate() {
+                // Only the head and the last two segments of the tail show
+                if hides_body && index > 0 && index + 2 < self.snake.len()
+                    || index >= shown_segments
+                {
+                    continue;
+                }
+                let symbol = match index {
+                    0 => self.glyphs.head(self.snake_direction),
+                    _ => body_symbol,
+                };
+                let cell = buf[((x * 2) + board_rect.x, y + board_rect.y)].set_symbol(symbol);
+                cell.set_fg(if self.rainbow {
+                    Game::RAINBOW[index % Game::RAINBOW.len()]
+                } else if self.eat_flash_ticks > 0 && index > 0 && index == self.snake.len() - 1 {
+                    Color::Yellow
+                } else if let Some(palette) = self.settings.palette {
+                    palette.color(index, self.snake.len(), self.truecolor)
+                } else if index == 0 {
+                    Color::LightGreen
+                } else {
+                    Color::Green
+                });
+            }
+
+            for popup in &self.popups {
+                let (x, y) = popup.position;
+                // Kept from running off the right of the board
+                let right = board_rect.x + self.width * 2;
+                let x = ((x * 2) + board_rect.x).min(right.saturating_sub(popup.text.len() as u16));
+                let mut style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+                if popup.fading() {
+                    style = style.add_modifier(Modifier::DIM);
+                }
+                buf.set_string(x, y + board_rect.y, &popup.text, style);
+            }
+
+            // Whatever the snake crashed into is picked out while it dies
+            if self.dying.is_some() {
+                let (x, y) = self.crash_cell();
+                buf[((x * 2) + board_rect.x, y + board_rect.y)].set_bg(Color::Red);
+            }
+
+            // The head flashes while a crash is being held off
+            if self.grace_tick && (self.last_moved.elapsed().as_millis() / 50).is_multiple_of(2) {
+                let (x, y) = self.snake[0];
+                buf[((x * 2) + board_rect.x, y + board_rect.y)].set_fg(Color::Red);
+            }
+
+            if let Some(rival) = &self.rival {
+                for (x, y) in &rival.body {
+                    buf[((x * 2) + board_rect.x, y + board_rect.y)]
+                        .set_symbol(self.glyphs.rival)
+                        .set_fg(Color::LightBlue);
+                }
+            }
+
+            if self.settings.fog {
+                self.render_fog(board_rect, buf);
+            }
+
+            // Nothing past the edge of a growing board shows until it's grown that far
+            if let Some(arena_rect) = growing_rect {
+                for x in 0..self.width {
+                    for y in 0..self.height {
+                        if !self.in_arena((x, y)) {
+                            buf[((x * 2) + board_rect.x, y + board_rect.y)].reset();
+                        }
+                    }
+                }
+                border.render(arena_rect, buf);
+            }
+
+            if let Some(debug) = &self.debug {
+                let (x, y) = debug.cursor;
+                buf[((x * 2) + board_rect.x, y + board_rect.y)].set_bg(Color::Yellow);
+                buf[((x * 2) + board_rect.x + 1, y + board_rect.y)].set_bg(Color::Yellow);
+            }
+
+            if let Some(message) = self.messages.front() {
+                // Keep out of the way of the head
+                let mut row = self.height / 2;
+                if self.snake[0].1 == row {
+                    row = row.checked_sub(1).unwrap_or(row + 1);
+                }
+                let message_rect =
+                    Rect::new(board_rect.x, board_rect.y + row, board_rect.width + 2, 1);
+                Line::from(message.as_str())
+                    .centered()
+                    .style(Style::new().fg(Color::LightGreen))
+                    .render(message_rect, buf);
+            }
+
+            if let Some(step) = self.tutorial.as_ref().and_then(Tutorial::step) {
+                // The first row near the top with nothing on it, so the snake and apple stay in view
+                let row = (1..self.height)
+                    .find(|&row| {
+                        self.snake.iter().all(|&(_, y)| y != row)
+                            && self.apples.iter().all(|apple| apple.position.1 != row)
+                    })
+                    .unwrap_or(0);
+                let instruction_rect =
+                    Rect::new(board_rect.x, board_rect.y + row, board_rect.width + 2, 1);
+                Line::from(format!(
+                    "{}  ({quit} to skip)",
+                    step.instruction(&self.settings.keymap, self.glyphs)
+                ))
+                .centered()
+                .style(Style::new().fg(Color::Yellow))
+                .render(instruction_rect, buf);
+            }
+
+            if let Some((text, _)) = &self.banner {
+                let banner_rect = Rect::new(
+                    board_rect.x,
+                    board_rect.y + board_rect.height / 2,
+                    board_rect.width + 2,
+                    1,
+                );
+                Clear.render(banner_rect, buf);
+                Line::from(text.as_str())
+                    .centered()
+                    .render(banner_rect, buf);
+            }
+
+            if let Some(started) = self.countdown {
+                self.render_countdown(started, board_rect, buf);
+            }
+
+            if self.paused_at.is_some() {
+                buf.set_style(border_rect, Style::new().add_modifier(Modifier::DIM));
+                let paused_rect = Rect::new(
+                    board_rect.x,
+                    board_rect.y + board_rect.height / 2,
+                    board_rect.width + 2,
+                    1,
+                );
+                Clear.render(paused_rect, buf);
+                Line::from(format!(
+                    "PAUSED {} press {} to resume",
+                    self.glyphs.dash,
+                    self.settings.keymap.key_name(Action::Pause, self.glyphs)
+                ))
+                .centered()
+                .render(paused_rect, buf);
+            }
+        }
+
+        // The play time, speed, and latest split go under the board, if there's room for them
+        let status_row = border_rect.bottom();
+        if !self.choosing_mode && !self.choosing_level && status_row < area.bottom() {
+            let mut spans = Vec::new();
+            // Timed modes count down in the title instead
+            if self.time_left().is_none() {
+                spans.push(Span::from(format_clock(self.play_time.as_secs())));
+            }
+            // Nokia has its own speed levels, shown with the mode
+            if self.mode() != Mode::Nokia {
+                if !spans.is_empty() {
+                    spans.push(Span::from("  "));
+                }
+                spans.push(Span::from(format!(
+                    "Speed: {}",
+                    speed_level(self.snake_move_time)
+                )));
+            }
+            if let Some(&split) = self.splits.last() {
+                let milestone = self.splits.len() as u32 * splits::SPLIT_SCORE;
+                if !spans.is_empty() {
+                    spans.push(Span::from("  "));
+                }
+                spans.push(Span::from(format!(
+                    "Split {milestone}: {}",
+                    format_split(split)
+                )));
+                if let Some(&best) = self.best_splits.get(self.splits.len() - 1) {
+                    let (sign, delta, color) = if split <= best {
+                        ("-", best - split, Color::Green)
+                    } else {
+                        ("+", split - best, Color::Red)
+                    };
+                    spans.push(Span::styled(
+                        format!(" ({sign}{:.1})", delta.as_secs_f64()),
+                        Style::new().fg(color),
+                    ));
+                }
+            }
+            let status_rect = Rect::new(border_rect.x, status_row, border_rect.width, 1);
+            Line::from(spans).centered().render(status_rect, buf);
+        }
+
+        // Then the keys that do something right now, leaving off the last ones if they don't fit
+        let hints_row = status_row + 1;
+        if hints_row < area.bottom() {
+            let mut hints = self.hints();
+            let text = loop {
+                let text = hints
+                    .iter()
+                    .map(|(key, action)| format!("{key} {action}"))
+                    .collect::<Vec<_>>()
+                    .join(self.glyphs.separator);
+                if text.chars().count() <= border_rect.width as usize || hints.pop().is_none() {
+                    break text;
+                }
+            };
+            let hints_rect = Rect::new(border_rect.x, hints_row, border_rect.width, 1);
+            Line::from(text)
+                .centered()
+                .style(Style::new().fg(Color::DarkGray))
+                .render(hints_rect, buf);
+        }
+
+        if let Some((achievement, _)) = self.achievement_banner {
+            let banner_rect = Rect::new(board_rect.x, board_rect.y, board_rect.width + 2, 1);
+            Clear.render(banner_rect, buf);
+            Line::from(format!("Unlocked: {}", achievement.name()))
+                .centered()
+                .style(Style::new().fg(Color::Yellow))
+                .render(banner_rect, buf);
+        }
+
+        if self.showing_achievements {
+            let list_rect = Rect::new(
+                board_rect.x,
+                board_rect.y,
+                board_rect.width + 2,
+                board_rect.height,
+            );
+            Clear.render(list_rect, buf);
+
+            let mut lines = vec![Line::from("Achievements"), Line::from("")];
+            for achievement in Achievement::ALL {
+                let mark = if self.achievements.is_unlocked(achievement) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                lines.push(Line::from(format!("{mark} {}", achievement.name())));
+                lines.push(Line::from(achievement.description()));
+            }
+            lines.extend([Line::from(""), Line::from("Press t to close")]);
+            Text::from(lines).centered().render(list_rect, buf);
+        }
+
+        if self.help_opened.is_some() {
+            self.render_help(border_rect, buf);
+        }
+
+        // Without color everything falls back to telling things apart by their symbols
+        if !self.colors {
+            for cell in &mut buf.content {
+                cell.set_fg(Color::Reset).set_bg(Color::Reset);
+            }
+        }
+    }
+}
+
+/// The digits of the countdown, one board cell to each character
+const BIG_DIGITS: [[&str; 5]; 3] = [
+    [" # ", "## ", " # ", " # ", "###"],
+    ["###", "  #", "###", "#  ", "###"],
+    ["###", "  #", "###", "  #", "###"],
+];
+
+/// Whether the terminal can show colors and the player hasn't asked for them to be left out
+fn colors_supported() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::env::var_os("TERM").is_none_or(|term| term != "dumb")
+}
+
+/// Whether the terminal says it can show any RGB color
+fn truecolor_supported() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit")
+}
+
+/// Whether the terminal is likely to have the block and arrow glyphs, which it will unless it's
+/// a dumb one or the locale says it isn't using UTF-8
+fn unicode_supported() -> bool {
+    if std::env::var_os("TERM").is_some_and(|term| term == "dumb") {
+        return false;
+    }
+
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .is_none_or(|locale| {
+            let locale = locale.to_uppercase();
+            locale.contains("UTF-8") || locale.contains("UTF8")
+        })
+}
+
+/// The biggest board that fits in the terminal with its border and the line under it, or `None`
+/// if the terminal can't be measured
+fn terminal_board_size() -> Option<(u16, u16)> {
+    let (columns, rows) = crossterm::terminal::size().ok()?;
+    let fit = |cells: u16| cells.clamp(*Settings::BOARD_SIZES.start(), Settings::MAX_FIT_SIZE);
+    // Each cell is two characters wide, and the board needs a cell's worth of room either side
+    Some((
+        fit((columns / 2).saturating_sub(3)),
+        fit(rows.saturating_sub(4)),
+    ))
+}
+
+/// Formats a number of seconds as minutes and seconds
+fn format_clock(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Formats a split time as minutes, seconds, and tenths
+fn format_split(split: Duration) -> String {
+    let tenths = split.as_millis() / 100;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// Scores are only compared with others played by the same rules, this names those rules
+fn score_bucket(settings: &Settings) -> String {
+    let mode = settings.mode.unwrap_or_default().name();
+    match settings.difficulty {
+        // Normal is what most games are played on, so it goes without saying
+        Difficulty::Normal => mode.to_string(),
+        difficulty => format!("{mode}-{}", difficulty.name()),
+    }
+}
+
+/// The first score after `score` that gets a milestone message
+fn next_milestone(score: u32) -> u32 {
+    match score {
+        0..10 => 10,
+        10..25 => 25,
+        _ => (score / 25 + 1) * 25,
+    }
+}
+
+/// Keeps the cells more than a few moves away from the head of `snake`, so a new apple can't
+/// be eaten without any play. Crowded boards with nothing that far away keep every cell
+fn away_from_head(snake: &[(u16, u16)], cells: Vec<(u16, u16)>) -> Vec<(u16, u16)> {
+    const MIN_DISTANCE: u16 = 3;
+
+    let head = snake[0];
+    let far: Vec<_> = cells
+        .iter()
+        .copied()
+        .filter(|cell| cell.0.abs_diff(head.0) + cell.1.abs_diff(head.1) > MIN_DISTANCE)
+        .collect();
+
+    if far.is_empty() { cells } else { far }
+}
+
+/// A cell from `free` exactly `BREADCRUMB_DISTANCE` cells from `from` in a straight line, in a
+/// random direction, or `None` if none of them are free
+fn breadcrumb_cell(
+    from: (u16, u16),
+    free: &[(u16, u16)],
+    rng: &mut impl Rng,
+) -> Option<(u16, u16)> {
+    const BREADCRUMB_DISTANCE: i16 = 5;
+
+    let (x, y) = (from.0 as i16, from.1 as i16);
+    let cells: Vec<_> = Direction::ALL
+        .into_iter()
+        .map(|direction| {
+            let (dx, dy) = direction.get_vec2();
+            (x + dx * BREADCRUMB_DISTANCE, y + dy * BREADCRUMB_DISTANCE)
+        })
+        .filter(|&(x, y)| x >= 0 && y >= 0)
+        .map(|(x, y)| (x as u16, y as u16))
+        .filter(|cell| free.contains(cell))
+        .collect();
+
+    cells.choose(rng).copied()
+}
+
+/// The cell one step from `from` towards `to`, closing whichever of the distances across and
+/// down is bigger first
+fn drift_toward(from: (u16, u16), to: (u16, u16)) -> (u16, u16) {
+    let step = |from: u16, to: u16| match from.cmp(&to) {
+        Ordering::Less => from + 1,
+        Ordering::Equal => from,
+        Ordering::Greater => from - 1,
+    };
+
+    if from.0.abs_diff(to.0) >= from.1.abs_diff(to.1) {
+        (step(from.0, to.0), from.1)
+    } else {
+        (from.0, step(from.1, to.1))
+    }
+}
+
+/// Points an apple is worth to a snake of `length`, one more for every 10 segments
+fn length_value(length: usize) -> u32 {
+    1 + (length / 10) as u32
+}
+
+/// Points an apple is worth at `move_time`, one more for every 25ms faster than the starting speed
+fn speed_value(move_time: u64) -> u32 {
+    1 + (Game::START_MOVE_TIME.saturating_sub(move_time) / 25) as u32
+}
+
+/// The speed shown to the player for `move_time`, level 1 at the normal starting speed and one
+/// more for every 10ms faster
+fn speed_level(move_time: u64) -> u64 {
+    1 + Game::START_MOVE_TIME.saturating_sub(move_time) / 10
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Down, Self::Left, Self::Right];
+
+    pub fn get_vec2(&self) -> (i16, i16) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+        }
+    }
+
+    pub fn clockwise(&self) -> Direction {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn counter_clockwise(&self) -> Direction {
+        self.clockwise().opposite()
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(args: &[&str]) -> Game {
+        let mut game =
+            Game::new(Settings::from_args(args.iter().map(|arg| arg.to_string())).unwrap());
+        // Nothing turns up by chance, so a tick only does what the test set up
+        game.pickup_chances.clear();
+        game.rng = StdRng::seed_from_u64(0);
+        // Or gets saved
+        game.achievements = Achievements::locked();
+        game
+    }
+
+    /// Lays the snake back and forth across an empty board, leaving the last `free` cells of the
+    /// path for the test to fill in. Returns those cells, the first one straight ahead
+    fn fill_board(game: &mut Game, free: usize) -> Vec<(u16, u16)> {
+        game.obstacles.clear();
+        game.patrollers.clear();
+        game.portals = None;
+        game.apples.clear();
+        game.pickups.clear();
+        game.mega_apple = None;
+        let mut path: Vec<_> = (0..game.height)
+            .flat_map(|y| {
+                let row = (0..game.width).map(move |x| (x, y));
+                if y % 2 == 0 {
+                    row.collect::<Vec<_>>()
+                } else {
+                    row.rev().collect()
+                }
+            })
+            .collect();
+        let rest = path.split_off(path.len() - free);
+        let (head, ahead) = (path[path.len() - 1], rest[0]);
+        game.snake_direction = if ahead.1 > head.1 {
+            Direction::Down
+        } else if ahead.0 > head.0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        };
+        path.reverse();
+        game.snake = path;
+        game.pending_growth = 1;
+        rest
+    }
+
+    #[test]
+    fn filling_the_board_wins() {
+        let mut game = game(&["--mode", "classic", "--board-size", "16"]);
+        let rest = fill_board(&mut game, 1);
+        game.apples.push(Apple::new(rest[0]));
+        game.tick();
+        assert!(game.won);
+        assert!(game.game_over);
+    }
+
+    #[test]
+    fn a_pickup_in_the_last_free_cell_isnt_a_win() {
+        let mut game = game(&["--mode", "classic", "--board-size", "16"]);
+        let rest = fill_board(&mut game, 2);
+        game.apples.push(Apple::new(rest[0]));
+        game.pickups.push(Pickup {
+            position: rest[1],
+            kind: PickupKind::Bonus,
+            ticks_left: 100,
+        });
+        game.tick();
+        assert!(game.apples.is_empty());
+        assert!(!game.won);
+        assert!(!game.game_over);
+    }
+
+    #[test]
+    fn a_partner_filling_the_last_cell_wins() {
+        let mut game = game(&["--mode", "classic", "--board-size", "16", "--coop"]);
+        let rest = fill_board(&mut game, 3);
+        game.apples.push(Apple::new(rest[1]));
+        let rival = game.rival.as_mut().unwrap();
+        rival.body = vec![rest[2]];
+        rival.direction = if rest[1].0 < rest[2].0 {
+            Direction::Left
+        } else {
+            Direction::Right
+        };
+        rival.pending_growth = 1;
+        game.tick();
+        assert!(game.won);
+        assert!(game.game_over);
+    }
+
+    #[test]
+    fn bounce_turns_out_of_every_corner() {
+        use Direction::*;
+
+        let mut game = game(&["--mode", "classic", "--bounce"]);
+        game.obstacles.clear();
+        game.portals = None;
+        let (right, bottom) = (game.width - 1, game.height - 1);
+        // Heading into a wall turns along the other one, heading away carries on
+        let expected = [
+            (
+                (0, 0),
+                [(Up, Right), (Left, Down), (Down, Down), (Right, Right)],
+            ),
+            (
+                (right, 0),
+                [(Up, Left), (Right, Down), (Down, Down), (Left, Left)],
+            ),
+            (
+                (0, bottom),
+                [(Down, Right), (Left, Up), (Up, Up), (Right, Right)],
+            ),
+            (
+                (right, bottom),
+                [(Down, Left), (Right, Up), (Up, Up), (Left, Left)],
+            ),
+        ];
+        for (corner, turns) in expected {
+            for (direction, turned) in turns {
+                // The body trails behind the head, when there's room for it
+                game.snake = [
+                    Some(corner),
+                    game.step(corner, direction.opposite().get_vec2()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+                game.snake_direction = direction;
+                game.bounce();
+                assert_eq!(game.snake_direction, turned, "{corner:?} {direction:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn faster_snakes_get_more_for_an_apple() {
+        assert_eq!(speed_value(Game::START_MOVE_TIME), 1);
+        assert_eq!(speed_value(Game::START_MOVE_TIME - 24), 1);
+        assert_eq!(speed_value(Game::START_MOVE_TIME - 25), 2);
+        assert_eq!(speed_value(Game::START_MOVE_TIME - 50), 3);
+        assert_eq!(speed_value(0), 1 + Game::START_MOVE_TIME as u32 / 25);
+        // Slower than the start, on easy say, is never worth less
+        assert_eq!(speed_value(Game::START_MOVE_TIME + 50), 1);
+
+        let mut game = game(&["--mode", "classic", "--speed-scoring"]);
+        game.snake_move_time = Game::START_MOVE_TIME - 50;
+        assert_eq!(game.apple_value(), 3);
+    }
+
+    #[test]
+    fn longer_snakes_get_more_for_an_apple() {
+        let mut game = game(&["--mode", "classic", "--length-scoring"]);
+        for (length, value) in [(1, 1), (9, 1), (10, 2), (19, 2), (20, 3), (30, 4)] {
+            assert_eq!(length_value(length), value, "{length}");
+            // The title's (+n) shows the same
+            game.snake = (0..length as u16)
+                .map(|x| (x % game.width, x / game.width))
+                .collect();
+            assert_eq!(game.apple_value(), value, "{length}");
+        }
+    }
+
+    #[test]
+    fn speed_level_goes_up_every_10ms() {
+        assert_eq!(speed_level(Game::START_MOVE_TIME), 1);
+        assert_eq!(speed_level(Game::START_MOVE_TIME - 9), 1);
+        assert_eq!(speed_level(Game::START_MOVE_TIME - 10), 2);
+        assert_eq!(speed_level(Game::START_MOVE_TIME - 100), 11);
+        assert_eq!(speed_level(0), 1 + Game::START_MOVE_TIME / 10);
+        // Easy starts slower than level 1
+        assert_eq!(speed_level(Game::START_MOVE_TIME + 50), 1);
+    }
+
+    #[test]
+    fn drift_closes_the_bigger_distance_first() {
+        assert_eq!(drift_toward((5, 5), (0, 3)), (4, 5));
+        assert_eq!(drift_toward((5, 5), (6, 9)), (5, 6));
+        // A tie goes across
+        assert_eq!(drift_toward((5, 5), (3, 3)), (4, 5));
+        // Along the edges, without going off them
+        assert_eq!(drift_toward((0, 0), (0, 7)), (0, 1));
+        assert_eq!(drift_toward((0, 7), (0, 0)), (0, 6));
+        assert_eq!(drift_toward((1, 0), (0, 0)), (0, 0));
+        assert_eq!(drift_toward((3, 3), (3, 3)), (3, 3));
+    }
+
+    #[test]
+    fn drifting_apples_stay_out_of_the_way() {
+        let mut game = game(&["--mode", "classic"]);
+        game.obstacles.clear();
+        game.portals = None;
+        game.snake = vec![(3, 5), (2, 5), (1, 5)];
+        game.apples = vec![Apple::new((5, 5)), Apple::new((3, 6)), Apple::new((9, 9))];
+        game.pickups = vec![Pickup {
+            position: (4, 5),
+            kind: PickupKind::Bonus,
+            ticks_left: 100,
+        }];
+        game.drift_apples();
+        let positions: Vec<_> = game.apples.iter().map(|apple| apple.position).collect();
+        // Blocked by the pickup, blocked by the head, and free to move
+        assert_eq!(positions, [(5, 5), (3, 6), (8, 9)]);
+    }
+
+    #[test]
+    fn new_apples_keep_away_from_the_head() {
+        let snake = [(5, 5), (4, 5)];
+        // Three moves away is still too close, four isn't
+        let cells = vec![(5, 6), (8, 5), (3, 4), (9, 5), (5, 1), (0, 0)];
+        assert_eq!(away_from_head(&snake, cells), [(9, 5), (5, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn a_crowded_board_keeps_the_near_cells() {
+        let snake = [(5, 5), (4, 5)];
+        let cells = vec![(5, 6), (8, 5), (3, 4)];
+        assert_eq!(away_from_head(&snake, cells.clone()), cells);
+        assert!(away_from_head(&snake, Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn breadcrumbs_land_five_cells_away_in_a_line() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let board: Vec<_> = (0..20).flat_map(|x| (0..20).map(move |y| (x, y))).collect();
+        for _ in 0..20 {
+            let cell = breadcrumb_cell((7, 7), &board, &mut rng).unwrap();
+            assert!(
+                [(2, 7), (12, 7), (7, 2), (7, 12)].contains(&cell),
+                "{cell:?}"
+            );
+            // Only right and down fit from the corner
+            let cell = breadcrumb_cell((0, 0), &board, &mut rng).unwrap();
+            assert!([(5, 0), (0, 5)].contains(&cell), "{cell:?}");
+            // And nothing fits off the far edges
+            let cell = breadcrumb_cell((19, 19), &board, &mut rng).unwrap();
+            assert!([(14, 19), (19, 14)].contains(&cell), "{cell:?}");
+        }
+    }
+
+    #[test]
+    fn breadcrumbs_need_a_free_cell() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(breadcrumb_cell((7, 7), &[], &mut rng), None);
+        // Free, but not in line
+        assert_eq!(breadcrumb_cell((7, 7), &[(8, 8), (3, 3)], &mut rng), None);
+        for _ in 0..10 {
+            assert_eq!(
+                breadcrumb_cell((7, 7), &[(8, 8), (7, 12)], &mut rng),
+                Some((7, 12))
+            );
+        }
+    }
+
+    #[test]
+    fn mashing_the_brake_waits_for_the_cooldown() {
+        let mut game = game(&["--mode", "classic", "--board-size", "30"]);
+        game.obstacles.clear();
+        game.snake = vec![(0, 0)];
+        game.snake_direction = Direction::Right;
+        game.brake();
+        for _ in 0..Game::BRAKE_COOLDOWN_TICKS - 1 {
+            game.tick();
+            game.brake();
+            assert!(game.brake_ticks < Game::BRAKE_TICKS);
+            // Keep the snake clear of the far wall
+            game.snake = vec![(0, 0)];
+        }
+        game.tick();
+        assert_eq!((game.brake_ticks, game.brake_cooldown), (0, 0));
+        game.brake();
+        assert_eq!(game.brake_ticks, Game::BRAKE_TICKS);
+    }
+
+    #[test]
+    fn a_small_terminal_holds_the_game_until_it_grows_back() {
+        let mut game = game(&["--mode", "classic"]);
+        let (width, height) = game.min_terminal_size();
+        game.countdown = None;
+        game.resize(width, height);
+        assert!(game.moving());
+
+        game.resize(width - 1, height);
+        assert!(!game.moving());
+        // Shrinking further doesn't let it go either
+        game.resize(width - 1, height - 1);
+        assert!(!game.moving());
+        assert!(game.countdown.is_none());
+
+        game.resize(width, height);
+        assert!(game.countdown.is_some());
+        assert!(!game.moving());
+        game.countdown = None;
+        assert!(game.moving());
+    }
+
+    #[test]
+    fn a_small_terminal_doesnt_resume_a_paused_game() {
+        let mut game = game(&["--mode", "classic"]);
+        let (width, height) = game.min_terminal_size();
+        game.countdown = None;
+        game.toggle_pause();
+        game.resize(width - 1, height);
+        game.resize(width, height);
+        assert!(game.countdown.is_none());
+        assert!(!game.moving());
+    }
+
+    #[test]
+    fn twin_apples_count_one_at_a_time() {
+        let mut game = game(&["--mode", "classic", "--levels"]);
+        game.obstacles.clear();
+        game.portals = None;
+        game.pickups.clear();
+        game.snake = vec![(5, 5), (4, 5), (3, 5)];
+        game.snake_direction = Direction::Right;
+        game.apples = vec![
+            Apple {
+                pair: Some(0),
+                ..Apple::new((6, 5))
+            },
+            Apple {
+                pair: Some(0),
+                ..Apple::new((6, 6))
+            },
+        ];
+        // The first of the two finishes the level
+        game.apples_eaten = Game::APPLES_PER_LEVEL - 1;
+        game.tick();
+        assert_eq!(game.apples_eaten, Game::APPLES_PER_LEVEL + 1);
+        assert_eq!(game.level, 2);
+
+        game.banner = None;
+        game.obstacles.clear();
+        game.patrollers.clear();
+        game.snake = vec![(5, 5), (4, 5), (3, 5)];
+        game.snake_direction = Direction::Right;
+        game.apples = vec![
+            Apple {
+                pair: Some(1),
+                ..Apple::new((6, 5))
+            },
+            Apple {
+                pair: Some(1),
+                ..Apple::new((6, 6))
+            },
+        ];
+        game.pickups.clear();
+        // Likewise the first of these is the golden apple's turn
+        game.apples_eaten = Game::APPLES_PER_GOLDEN - 1;
+        game.tick();
+        assert_eq!(game.apples_eaten, Game::APPLES_PER_GOLDEN + 1);
+        assert!(
+            game.pickups
+                .iter()
+                .any(|pickup| pickup.kind == PickupKind::Golden)
+        );
+    }
+
+    /// A short snake about to go over the right edge of the board, with a phase charge to spare
+    fn phasing_game(args: &[&str]) -> Game {
+        let mut game = game(args);
+        game.obstacles.clear();
+        game.portals = None;
+        game.apples.clear();
+        game.phases = 1;
+        let right = game.width - 1;
+        game.snake = vec![(right, 5), (right - 1, 5), (right - 2, 5)];
+        game.snake_direction = Direction::Right;
+        game
+    }
+
+    #[test]
+    fn phasing_through_the_border_uses_a_charge() {
+        let mut game = phasing_game(&["--mode", "classic", "--phases"]);
+        game.tick();
+        assert_eq!(game.snake[0], (0, 5));
+        assert_eq!(game.phases, 0);
+    }
+
+    #[test]
+    fn phasing_into_a_wall_keeps_the_charge() {
+        let mut game = phasing_game(&["--mode", "classic", "--phases", "--forgiving"]);
+        game.obstacles.insert((0, 5));
+        // Held for the grace tick, then crashing
+        game.tick();
+        assert!(game.grace_tick);
+        assert_eq!(game.phases, 1);
+        game.tick();
+        assert!(game.game_over);
+        assert_eq!(game.phases, 1);
+    }
+
+    #[test]
+    fn rewinding_undoes_an_apple_and_everything_after_it() {
+        let mut game = game(&["--mode", "practice"]);
+        game.obstacles.clear();
+        game.portals = None;
+        game.snake = vec![(5, 5), (4, 5), (3, 5)];
+        game.snake_direction = Direction::Right;
+        game.apples = vec![Apple::new((6, 5))];
+        game.pickups = vec![Pickup {
+            position: (9, 9),
+            kind: PickupKind::Bonus,
+            ticks_left: 100,
+        }];
+        game.boost_ticks = 3;
+        game.apples_eaten = Game::APPLES_PER_GOLDEN - 1;
+        let lives = game.lives;
+
+        game.tick();
+        assert_eq!(game.apples_eaten, Game::APPLES_PER_GOLDEN);
+        assert_eq!(game.pickups.len(), 2);
+        game.rewind();
+
+        assert_eq!(game.snake, [(5, 5), (4, 5), (3, 5)]);
+        assert_eq!(game.score, 0);
+        assert_eq!(game.apples_eaten, Game::APPLES_PER_GOLDEN - 1);
+        assert_eq!(game.pickups.len(), 1);
+        assert_eq!(game.pickups[0].ticks_left, 100);
+        assert_eq!(game.boost_ticks, 3);
+        assert_eq!((game.ticks, game.play_time), (0, Duration::ZERO));
+        assert_eq!(game.lives, lives);
+        assert_eq!(game.history.len(), 0);
+    }
+
+    #[test]
+    fn respawning_slows_down_by_the_difficultys_step() {
+        for difficulty in Difficulty::ALL {
+            let mut game = game(&[
+                "--mode",
+                "classic",
+                "--lives",
+                "--difficulty",
+                difficulty.name(),
+            ]);
+            game.snake_move_time = 100;
+            game.die();
+            assert_eq!(
+                game.snake_move_time,
+                100 + Game::RESPAWN_SLOWDOWN_STEPS * difficulty.speed_step(),
+                "{}",
+                difficulty.name()
+            );
+        }
+    }
+
+    #[test]
+    fn respawning_keeps_clear_of_the_rival() {
+        let mut game = game(&["--mode", "classic", "--lives", "--ai"]);
+        game.obstacles.clear();
+        game.portals = None;
+        game.apples.clear();
+        // Right through the middle, where the snake would usually come back
+        let middle = game.height / 2;
+        let rival = game.rival.as_mut().unwrap();
+        rival.body = (middle - 1..=middle + 1)
+            .flat_map(|y| (0..game.width).map(move |x| (x, y)))
+            .collect();
+        game.die();
+
+        let (x, y) = game.snake[0];
+        let runway: Vec<_> = (x..=x + Game::SPAWN_RUNWAY).map(|x| (x, y)).collect();
+        let rival = game.rival.as_ref().unwrap();
+        assert!(
+            runway.iter().all(|cell| !rival.body.contains(cell)),
+            "{runway:?}"
+        );
+    }
+
+    #[test]
+    fn sprinting_at_the_lowest_floor_still_takes_time() {
+        let mut game = game(&["--mode", "classic", "--speed-floor", "1"]);
+        game.obstacles.clear();
+        game.portals = None;
+        game.snake_move_time = 1;
+        game.sprinting = true;
+        game.boost_ticks = 5;
+        assert_eq!(game.move_interval(), Duration::from_millis(1));
+
+        game.show_message("Nice!".to_string());
+        assert_eq!(game.message_expires, game.ticks + 1000);
+        game.tick();
+        assert_eq!(game.messages.len(), 1);
+    }
+
+    #[test]
+    fn no_mega_apple_without_room_for_one() {
+        let mut game = game(&["--mode", "classic"]);
+        game.portals = None;
+        game.apples.clear();
+        game.snake = vec![(0, 0)];
+        game.obstacles = (0..game.width)
+            .flat_map(|x| (0..game.height).map(move |y| (x, y)))
+            .filter(|(x, y)| (x + y) % 2 == 1)
+            .collect();
+        for _ in 0..1000 {
+            game.update_mega_apple();
+        }
+        assert!(game.mega_apple.is_none());
+    }
+
+    /// The text of the help box, row by row
+    fn help_text(game: &Game) -> String {
+        let area = Rect::new(0, 0, 60, 30);
+        let mut buf = Buffer::empty(area);
+        game.render_help(area, &mut buf);
+        buf.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn help_lists_player_twos_keys_in_versus() {
+        assert!(!help_text(&game(&["--mode", "classic"])).contains("Player two"));
+        let versus = game(&["--mode", "classic", "--versus"]);
+        assert!(help_text(&versus).contains("w/s/a/d     Player two"));
+    }
+
+    #[test]
+    fn debug_cursor_leaves_steering_keys_alone() {
+        let mut game = game(&["--mode", "classic", "--debug"]);
+        let cursor = game.debug.as_ref().unwrap().cursor;
+        for key in "hjkl".chars() {
+            assert!(!game.debug_key(KeyCode::Char(key)));
+        }
+        assert_eq!(game.debug.as_ref().unwrap().cursor, cursor);
+
+        assert!(game.debug_key(KeyCode::Char('J')));
+        assert_eq!(
+            game.debug.as_ref().unwrap().cursor,
+            (cursor.0 - 1, cursor.1)
+        );
     }
 }
@@ -0,0 +1,94 @@
+use std::{collections::HashSet, fs};
+
+use anyhow::{Context, Result, bail, ensure};
+
+/// Maps shipped with the game, selectable by name
+pub const BUNDLED: [(&str, &str); 3] = [
+    ("arena", include_str!("../maps/arena.txt")),
+    ("spiral", include_str!("../maps/spiral.txt")),
+    ("rooms", include_str!("../maps/rooms.txt")),
+];
+
+/// A board read from plain text, where `#` is a wall, `.` is floor, and `S` is the snake's spawn
+#[derive(Debug, Clone)]
+pub struct Maze {
+    pub width: u16,
+    pub height: u16,
+    pub walls: HashSet<(u16, u16)>,
+    pub spawn: (u16, u16),
+}
+
+impl Maze {
+    const MAX_SIZE: usize = 100;
+    /// Floor cells needed besides the spawn so there's somewhere to put apples
+    const MIN_FLOOR: usize = 2;
+
+    /// Loads a bundled map by name, or otherwise reads the map file at `name`
+    pub fn load(name: &str) -> Result<Self> {
+        match BUNDLED.iter().find(|(bundled, _)| *bundled == name) {
+            Some((_, layout)) => Self::parse(layout),
+            None => {
+                let layout = fs::read_to_string(name)
+                    .with_context(|| format!("Couldn't read map file {name}"))?;
+                Self::parse(&layout).with_context(|| format!("Invalid map file {name}"))
+            }
+        }
+    }
+
+    pub fn parse(layout: &str) -> Result<Self> {
+        let rows: Vec<_> = layout
+            .trim_end()
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        ensure!(width > 0, "The map is empty");
+        ensure!(
+            width <= Self::MAX_SIZE && rows.len() <= Self::MAX_SIZE,
+            "The map can be at most {0}x{0}",
+            Self::MAX_SIZE
+        );
+
+        let mut walls = HashSet::new();
+        let mut spawn = None;
+        let mut floor = 0;
+
+        for (y, row) in rows.iter().enumerate() {
+            ensure!(
+                row.chars().count() == width,
+                "Line {} is {} cells wide but the first line is {width}",
+                y + 1,
+                row.chars().count()
+            );
+
+            for (x, cell) in row.chars().enumerate() {
+                let position = (x as u16, y as u16);
+                match cell {
+                    '#' => {
+                        walls.insert(position);
+                    }
+                    '.' => floor += 1,
+                    'S' => ensure!(
+                        spawn.replace(position).is_none(),
+                        "The map has more than one spawn S"
+                    ),
+                    _ => bail!("Unexpected {cell:?} on line {}, expected #, . or S", y + 1),
+                }
+            }
+        }
+
+        let spawn = spawn.context("The map has no spawn S")?;
+        ensure!(
+            floor >= Self::MIN_FLOOR,
+            "The map needs at least {} floor cells for apples",
+            Self::MIN_FLOOR
+        );
+
+        Ok(Self {
+            width: width as u16,
+            height: rows.len() as u16,
+            walls,
+            spawn,
+        })
+    }
+}
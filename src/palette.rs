@@ -0,0 +1,62 @@
+use ratatui::style::Color;
+
+/// Colors the snake fades through from its head to its tail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    Forest,
+    Sunset,
+    Ocean,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Self::Forest, Self::Sunset, Self::Ocean];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Forest => "forest",
+            Self::Sunset => "sunset",
+            Self::Ocean => "ocean",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|palette| palette.name() == name)
+    }
+
+    /// The head and tail colors, blended between on terminals with full RGB colors
+    fn ends(&self) -> ((u8, u8, u8), (u8, u8, u8)) {
+        match self {
+            Self::Forest => ((170, 255, 120), (10, 70, 20)),
+            Self::Sunset => ((255, 230, 90), (120, 0, 60)),
+            Self::Ocean => ((150, 240, 255), (0, 30, 110)),
+        }
+    }
+
+    /// The same fade in the 256 color palette, for terminals without full RGB colors
+    fn indexed(&self) -> &'static [u8] {
+        match self {
+            Self::Forest => &[156, 120, 83, 70, 34, 28, 22],
+            Self::Sunset => &[227, 221, 215, 209, 167, 125, 89],
+            Self::Ocean => &[159, 123, 81, 39, 33, 26, 18],
+        }
+    }
+
+    /// The color of segment `index` of a snake `length` long. The head is always the brightest
+    /// and the tail the darkest, however long the snake gets
+    pub fn color(&self, index: usize, length: usize, truecolor: bool) -> Color {
+        let along = if length > 1 {
+            index as f64 / (length - 1) as f64
+        } else {
+            0.0
+        };
+
+        if truecolor {
+            let ((r1, g1, b1), (r2, g2, b2)) = self.ends();
+            let blend = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * along) as u8;
+            Color::Rgb(blend(r1, r2), blend(g1, g2), blend(b1, b2))
+        } else {
+            let colors = self.indexed();
+            Color::Indexed(colors[(along * (colors.len() - 1) as f64).round() as usize])
+        }
+    }
+}
@@ -0,0 +1,55 @@
+/// Which way a patroller slides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// An obstacle that slides back and forth along one axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Patroller {
+    pub position: (u16, u16),
+    pub axis: Axis,
+    /// Whether it's heading right or down, rather than left or up
+    pub forward: bool,
+}
+
+impl Patroller {
+    pub fn new(position: (u16, u16), axis: Axis) -> Self {
+        Self {
+            position,
+            axis,
+            forward: true,
+        }
+    }
+
+    /// The cell it moves to next, or `None` if that's off a `width`x`height` board
+    fn ahead(&self, width: u16, height: u16) -> Option<(u16, u16)> {
+        let (x, y) = self.position;
+        let (x, y) = match (self.axis, self.forward) {
+            (Axis::Horizontal, true) => (x.checked_add(1)?, y),
+            (Axis::Horizontal, false) => (x.checked_sub(1)?, y),
+            (Axis::Vertical, true) => (x, y.checked_add(1)?),
+            (Axis::Vertical, false) => (x, y.checked_sub(1)?),
+        };
+
+        (x < width && y < height).then_some((x, y))
+    }
+
+    /// Moves a cell along, turning around instead at the edge of the board or anything
+    /// `is_blocked`. A cell the snake `is_snake` on is waited out, so the snake is never run
+    /// over from behind
+    pub fn step(
+        &mut self,
+        width: u16,
+        height: u16,
+        is_blocked: impl Fn((u16, u16)) -> bool,
+        is_snake: impl Fn((u16, u16)) -> bool,
+    ) {
+        match self.ahead(width, height) {
+            Some(cell) if is_snake(cell) => {}
+            Some(cell) if !is_blocked(cell) => self.position = cell,
+            _ => self.forward = !self.forward,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use ratatui::style::Color;
+
+/// A short lived item on the board with an effect other than growing the snake
+#[derive(Debug, Clone)]
+pub struct Pickup {
+    pub position: (u16, u16),
+    pub kind: PickupKind,
+    /// Move ticks until the pickup moves somewhere else or disappears
+    pub ticks_left: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    /// Removes two segments from the tail
+    Poison,
+    /// Worth extra score, only around for a short time
+    Golden,
+    /// Temporarily speeds the snake up
+    SpeedBoost,
+    /// Temporarily slows the snake down
+    SlowDown,
+    /// Lets the snake pass through itself for a while
+    Ghost,
+    /// Trims a few segments off a long snake without costing score
+    Shrink,
+    /// Reverses the controls for a while
+    Curse,
+    /// Swaps the head and the tail around
+    Reverse,
+    /// Worth a lot of score without growing the snake, stays until it's eaten
+    Bonus,
+    /// Pulls the apples towards the head for a while
+    Magnet,
+    /// Holds the snake at a steady speed for a while, however fast it had got
+    Freeze,
+    /// Looks almost like an apple, but costs score instead
+    Decoy,
+}
+
+impl PickupKind {
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Poison => Color::Magenta,
+            Self::Golden => Color::Yellow,
+            Self::SpeedBoost => Color::Cyan,
+            Self::SlowDown => Color::Blue,
+            Self::Ghost => Color::Gray,
+            Self::Shrink => Color::LightGreen,
+            Self::Curse => Color::Red,
+            Self::Reverse => Color::LightCyan,
+            Self::Bonus => Color::LightRed,
+            Self::Magnet => Color::LightMagenta,
+            Self::Freeze => Color::White,
+            Self::Decoy => Color::LightRed,
+        }
+    }
+
+    /// Whether the pickup jumps to a new cell when its lifetime runs out instead of disappearing
+    pub fn relocates(&self) -> bool {
+        matches!(self, Self::Poison)
+    }
+
+    /// Whether the pickup stays where it is until it's eaten
+    pub fn persists(&self) -> bool {
+        matches!(self, Self::Bonus)
+    }
+
+    /// Move ticks the pickup stays in one place, unless it persists
+    pub fn lifetime(&self) -> u32 {
+        match self {
+            Self::Poison => 60,
+            Self::Golden => 40,
+            Self::SpeedBoost | Self::SlowDown | Self::Shrink => 50,
+            Self::Ghost
+            | Self::Curse
+            | Self::Reverse
+            | Self::Bonus
+            | Self::Magnet
+            | Self::Freeze
+            | Self::Decoy => 40,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+/// Points scored, floating up from where they were scored
+#[derive(Debug)]
+pub struct Popup {
+    pub position: (u16, u16),
+    pub text: String,
+    /// Move ticks until the popup disappears
+    pub ticks_left: u32,
+}
+
+impl Popup {
+    const TICKS: u32 = 4;
+
+    pub fn new(position: (u16, u16), points: u32) -> Self {
+        Self {
+            position,
+            text: format!("+{points}"),
+            ticks_left: Self::TICKS,
+        }
+    }
+
+    /// Floats a row further up, staying on the top row of the board once it gets there
+    pub fn rise(&mut self) {
+        self.position.1 = self.position.1.saturating_sub(1);
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+    }
+
+    /// Whether it's about to disappear, so it's drawn fading out
+    pub fn fading(&self) -> bool {
+        self.ticks_left <= 1
+    }
+}
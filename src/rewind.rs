@@ -0,0 +1,78 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+use crate::{
+    Direction,
+    apple::{Apple, MegaApple},
+    patrol::Patroller,
+    pickup::Pickup,
+};
+
+/// Enough of the game to put it back how it was a tick ago
+#[derive(Debug)]
+pub struct Snapshot {
+    pub snake: Vec<(u16, u16)>,
+    pub direction: Direction,
+    pub apples: Vec<Apple>,
+    pub patrollers: Vec<Patroller>,
+    pub move_time: u64,
+    pub score: u32,
+    pub pending_growth: usize,
+    pub pickups: Vec<Pickup>,
+    pub mega_apple: Option<MegaApple>,
+    pub mines: HashSet<(u16, u16)>,
+    /// The corners of the arena, which grows and shrinks in some games
+    pub bounds: ((u16, u16), (u16, u16)),
+    pub apples_eaten: u32,
+    pub ticks_since_apple: u32,
+    pub combo: u32,
+    pub lives: u32,
+    pub phases: u32,
+    pub ticks: u32,
+    pub play_time: Duration,
+    pub effects: Effects,
+}
+
+/// Move ticks left on everything that wears off
+#[derive(Debug)]
+pub struct Effects {
+    pub boost: u32,
+    pub slow: u32,
+    pub freeze: u32,
+    pub magnet: u32,
+    pub ghost: u32,
+    pub ghost_grace_used: bool,
+    pub reversed: u32,
+    pub brake: u32,
+    pub brake_cooldown: u32,
+    pub sprinting: bool,
+}
+
+/// The last few ticks of a practice game, oldest first
+#[derive(Debug, Default)]
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl History {
+    pub const CAPACITY: usize = 50;
+
+    /// Records a snapshot, forgetting the oldest one once it's full
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() == Self::CAPACITY {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    /// How many ticks can still be rewound
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+}
@@ -0,0 +1,55 @@
+use crate::Direction;
+
+/// The second snake on the board when playing against someone
+#[derive(Debug)]
+pub struct Rival {
+    /// Empty while a computer snake is waiting to respawn
+    pub body: Vec<(u16, u16)>,
+    pub direction: Direction,
+    pub score: u32,
+    /// Segments still to be added, one per move
+    pub pending_growth: usize,
+    pub controller: Controller,
+    /// Move ticks until a dead computer snake comes back
+    pub respawn_ticks: u32,
+}
+
+/// Who steers the second snake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// A second player on WASD, the game ends when either snake dies
+    Human,
+    /// The built-in AI, which respawns after dying
+    Computer,
+    /// A second player on WASD on the same side, sharing the score and ending the game for
+    /// both if either snake dies
+    Partner,
+    /// Steered by the player's own keys with left and right swapped, otherwise like a partner
+    Twin,
+}
+
+impl Controller {
+    /// Whether the two snakes play together, sharing the score and the game ending for both
+    pub fn is_teammate(&self) -> bool {
+        matches!(self, Self::Partner | Self::Twin)
+    }
+}
+
+/// How a versus game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    PlayerOneWins,
+    PlayerTwoWins,
+    /// Both snakes died on the same tick
+    Draw,
+}
+
+impl Outcome {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::PlayerOneWins => "Player 1 wins",
+            Self::PlayerTwoWins => "Player 2 wins",
+            Self::Draw => "Draw",
+        }
+    }
+}
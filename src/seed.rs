@@ -0,0 +1,15 @@
+/// Longest code that can be typed in for a seed
+pub const MAX_CODE_LENGTH: usize = 12;
+
+/// Whether `c` can be part of a seed code
+pub fn is_code_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+/// Turns a code typed in by the player into a seed, the same on every machine and ignoring case
+pub fn from_code(code: &str) -> u64 {
+    // FNV-1a, which unlike the std hasher is guaranteed not to change between releases
+    code.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte.to_ascii_uppercase())).wrapping_mul(0x0100_0000_01b3)
+    })
+}
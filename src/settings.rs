@@ -0,0 +1,701 @@
+use std::ops::RangeInclusive;
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    Direction, generator::Generator, keymap::Keymap, layout::Layout, maze::Maze, palette::Palette,
+    rival::Controller, seed, speed::SpeedCurve,
+};
+
+/// Options chosen at startup that survive restarts
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// `None` until a mode is picked, in which case the game asks before starting
+    pub mode: Option<Mode>,
+    pub difficulty: Difficulty,
+    pub border_mode: BorderMode,
+    pub layout: Layout,
+    /// Walls made up fresh for each game, on top of the layout
+    pub generator: Option<Generator>,
+    /// Map to play on instead of the default empty board
+    pub maze: Option<Maze>,
+    /// How many walls are scattered across the board at random
+    pub random_walls: usize,
+    /// Mines pile up on the board as the game goes on
+    pub mines: bool,
+    /// How many apples are on the board at once
+    pub apple_count: usize,
+    /// How many times the snake can die before the game ends
+    pub lives: u32,
+    /// How many segments the snake grows by for every apple
+    pub growth_per_apple: usize,
+    /// How many segments the snake starts with, as many as fit
+    pub starting_length: usize,
+    /// Apples left uneaten for too long jump to another cell
+    pub relocate_apples: bool,
+    /// Every few apples the game moves on to a new level with more walls
+    pub levels: bool,
+    /// The board starts small and grows a ring at a time as apples are eaten
+    pub growing: bool,
+    /// Apples eaten in quick succession are worth more
+    pub combos: bool,
+    /// Each new apple is put a fixed distance from the head, leaving a trail to follow
+    pub breadcrumbs: bool,
+    /// Apples are worth more the faster the snake is moving
+    pub speed_scoring: bool,
+    /// Going too long without an apple costs points
+    pub score_decay: bool,
+    /// How the snake speeds up, or `None` for the mode's usual curve
+    pub speed_curve: Option<SpeedCurve>,
+    /// Fastest move time in milliseconds, or `None` for the mode's usual one
+    pub speed_floor: Option<u64>,
+    /// Move time in milliseconds at the start of the game, or `None` for the difficulty's usual
+    /// one. Modes with their own speeds ignore it
+    pub start_speed: Option<u64>,
+    /// Width and height of the board, or `None` for the difficulty's usual size
+    pub board_size: Option<u16>,
+    /// The board is as wide and tall as the terminal has room for, up to `Settings::MAX_FIT_SIZE`
+    pub fit_terminal: bool,
+    /// Apples are worth more the longer the snake is
+    pub length_scoring: bool,
+    /// The snake keeps getting faster past the usual top speed, for double points
+    pub unbounded_speed: bool,
+    /// A crash into a wall or the snake itself waits a tick, giving the player a chance to turn
+    pub forgiving: bool,
+    /// The snake can pass through the border to the other side a couple of times
+    pub phases: bool,
+    /// Which of the controls are swapped around, if any
+    pub mirror: Option<Mirror>,
+    /// Every so often the apples are shaken loose to somewhere else on the board
+    pub earthquakes: bool,
+    /// Only the board around the snake's head can be seen
+    pub fog: bool,
+    /// The board is shaded like a checkerboard, so it's easier to count cells
+    pub grid: bool,
+    /// Everything is drawn in plain ASCII, for terminals that garble anything else
+    pub ascii: bool,
+    /// Colors the snake fades through, or `None` for plain green
+    pub palette: Option<Palette>,
+    /// Clicking on the board steers towards where was clicked. Off by default, since it gets in
+    /// the way of selecting text in the terminal
+    pub mouse: bool,
+    /// Which keys steer, sprint, brake, pause, restart, and quit
+    pub keymap: Keymap,
+    /// Everyone playing on the same day gets the same board
+    pub daily: bool,
+    /// Code for a board to share with someone else, which takes over from the daily one
+    pub seed: Option<String>,
+    /// Walks through the controls before the first game
+    pub tutorial: bool,
+    /// Cheat keys for trying things out, with nothing about the game saved
+    pub debug: bool,
+    /// Which of the fixed speeds Nokia mode plays at, from 1 to `MAX_SPEED_LEVEL`, or `None` to
+    /// pick one before the game
+    pub speed_level: Option<u32>,
+    /// Who controls the second snake, if there is one
+    pub opponent: Option<Controller>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            difficulty: Difficulty::default(),
+            border_mode: BorderMode::default(),
+            layout: Layout::default(),
+            generator: None,
+            maze: None,
+            random_walls: 0,
+            mines: false,
+            apple_count: 1,
+            lives: 1,
+            growth_per_apple: 1,
+            starting_length: 1,
+            relocate_apples: false,
+            levels: false,
+            growing: false,
+            combos: false,
+            breadcrumbs: false,
+            speed_scoring: false,
+            score_decay: false,
+            speed_curve: None,
+            speed_floor: None,
+            start_speed: None,
+            board_size: None,
+            fit_terminal: false,
+            length_scoring: false,
+            unbounded_speed: false,
+            forgiving: false,
+            phases: false,
+            mirror: None,
+            earthquakes: false,
+            fog: false,
+            grid: false,
+            ascii: false,
+            palette: None,
+            mouse: false,
+            keymap: Keymap::default(),
+            daily: false,
+            seed: None,
+            tutorial: false,
+            debug: false,
+            speed_level: None,
+            opponent: None,
+        }
+    }
+}
+
+impl Settings {
+    const MAX_GROWTH: usize = 5;
+    pub const MAX_SPEED_LEVEL: u32 = 9;
+    pub const START_SPEEDS: RangeInclusive<u64> = 40..=400;
+    /// Anything smaller doesn't leave room for the menus
+    pub const BOARD_SIZES: RangeInclusive<u16> = 16..=30;
+    /// Past this a board sized to the terminal takes too long to cross
+    pub const MAX_FIT_SIZE: u16 = 50;
+
+    /// The width and height of the board, unless a map says otherwise
+    pub fn board_size(&self) -> u16 {
+        self.board_size
+            .unwrap_or_else(|| self.difficulty.board_size())
+    }
+
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut settings = Self::default();
+
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mode" => {
+                    let name = args.next().context("--mode expects a mode name")?;
+                    settings.mode = Some(Mode::from_name(&name).with_context(|| {
+                        let names: Vec<_> = Mode::ALL.iter().map(Mode::name).collect();
+                        format!("Unknown mode {name}, expected one of {}", names.join(", "))
+                    })?);
+                }
+                "--difficulty" => {
+                    let name = args
+                        .next()
+                        .context("--difficulty expects a difficulty name")?;
+                    settings.difficulty = Difficulty::from_name(&name).with_context(|| {
+                        let names: Vec<_> = Difficulty::ALL.iter().map(Difficulty::name).collect();
+                        format!(
+                            "Unknown difficulty {name}, expected one of {}",
+                            names.join(", ")
+                        )
+                    })?;
+                }
+                "--wrap" => settings.border_mode = BorderMode::Wrap,
+                "--bounce" => settings.border_mode = BorderMode::Bounce,
+                "--relocate-apples" => settings.relocate_apples = true,
+                "--levels" => settings.levels = true,
+                "--growing" => settings.growing = true,
+                "--mines" => settings.mines = true,
+                "--combos" => settings.combos = true,
+                "--breadcrumbs" => settings.breadcrumbs = true,
+                "--score-decay" => settings.score_decay = true,
+                "--speed-scoring" => settings.speed_scoring = true,
+                "--length-scoring" => settings.length_scoring = true,
+                "--unbounded" => settings.unbounded_speed = true,
+                "--forgiving" => settings.forgiving = true,
+                "--phases" => settings.phases = true,
+                "--daily" => settings.daily = true,
+                "--seed" => {
+                    let code = args.next().context("--seed expects a code")?;
+                    if code.is_empty()
+                        || code.len() > seed::MAX_CODE_LENGTH
+                        || !code.chars().all(seed::is_code_char)
+                    {
+                        bail!(
+                            "--seed expects up to {} letters and numbers",
+                            seed::MAX_CODE_LENGTH
+                        );
+                    }
+                    settings.seed = Some(code);
+                }
+                "--tutorial" => settings.tutorial = true,
+                "--debug" => settings.debug = true,
+                "--fog" => settings.fog = true,
+                "--grid" => settings.grid = true,
+                "--ascii" => settings.ascii = true,
+                "--mouse" => settings.mouse = true,
+                "--earthquakes" => settings.earthquakes = true,
+                "--mirror" => settings.mirror = Some(Mirror::LeftRight),
+                "--mirror-all" => settings.mirror = Some(Mirror::All),
+                "--versus" => settings.opponent = Some(Controller::Human),
+                "--ai" => settings.opponent = Some(Controller::Computer),
+                "--coop" => settings.opponent = Some(Controller::Partner),
+                "--twin" => settings.opponent = Some(Controller::Twin),
+                "--generator" => {
+                    let name = args
+                        .next()
+                        .context("--generator expects a generator name")?;
+                    settings.generator = Some(Generator::from_name(&name).with_context(|| {
+                        let names: Vec<_> = Generator::ALL.iter().map(Generator::name).collect();
+                        format!(
+                            "Unknown generator {name}, expected one of {}",
+                            names.join(", ")
+                        )
+                    })?);
+                }
+                "--layout" => {
+                    let name = args.next().context("--layout expects a layout name")?;
+                    settings.layout = Layout::from_name(&name).with_context(|| {
+                        let names: Vec<_> = Layout::ALL.iter().map(Layout::name).collect();
+                        format!(
+                            "Unknown layout {name}, expected one of {}",
+                            names.join(", ")
+                        )
+                    })?;
+                }
+                "--map" => {
+                    let map = args
+                        .next()
+                        .context("--map expects a bundled map name or a path")?;
+                    settings.maze = Some(Maze::load(&map)?);
+                }
+                "--apples" => {
+                    // The count is optional, plain `--apples` gives the default multi-apple mode
+                    settings.apple_count =
+                        match args.next_if(|count| count.parse::<usize>().is_ok()) {
+                            Some(count) => count.parse()?,
+                            None => 3,
+                        };
+                    if settings.apple_count == 0 {
+                        bail!("--apples expects at least one apple");
+                    }
+                }
+                "--random-walls" => {
+                    // Like `--apples`, the count is optional
+                    settings.random_walls =
+                        match args.next_if(|count| count.parse::<usize>().is_ok()) {
+                            Some(count) => count.parse()?,
+                            None => 15,
+                        };
+                }
+                "--lives" => {
+                    settings.lives = match args.next_if(|count| count.parse::<u32>().is_ok()) {
+                        Some(count) => count.parse()?,
+                        None => 3,
+                    };
+                    if settings.lives == 0 {
+                        bail!("--lives expects at least one life");
+                    }
+                }
+                "--palette" => {
+                    let name = args.next().context("--palette expects a palette name")?;
+                    settings.palette = Some(Palette::from_name(&name).with_context(|| {
+                        let names: Vec<_> = Palette::ALL.iter().map(Palette::name).collect();
+                        format!(
+                            "Unknown palette {name}, expected one of {}",
+                            names.join(", ")
+                        )
+                    })?);
+                }
+                "--speed-curve" => {
+                    let name = args.next().context("--speed-curve expects a curve name")?;
+                    settings.speed_curve =
+                        Some(SpeedCurve::from_name(&name).with_context(|| {
+                            format!(
+                                "Unknown speed curve {name}, expected one of {}",
+                                SpeedCurve::NAMES.join(", ")
+                            )
+                        })?);
+                }
+                "--speed-floor" => {
+                    let floor = args
+                        .next()
+                        .context("--speed-floor expects a move time in milliseconds")?;
+                    let floor: u64 = floor
+                        .parse()
+                        .with_context(|| format!("Invalid speed floor {floor}"))?;
+                    if floor == 0 {
+                        bail!("--speed-floor expects at least 1 millisecond");
+                    }
+                    settings.speed_floor = Some(floor);
+                }
+                "--start-speed" => {
+                    let speed = args
+                        .next()
+                        .context("--start-speed expects a move time in milliseconds")?;
+                    let speed: u64 = speed
+                        .parse()
+                        .with_context(|| format!("Invalid start speed {speed}"))?;
+                    if !Self::START_SPEEDS.contains(&speed) {
+                        bail!(
+                            "--start-speed expects {} to {} milliseconds",
+                            Self::START_SPEEDS.start(),
+                            Self::START_SPEEDS.end()
+                        );
+                    }
+                    settings.start_speed = Some(speed);
+                }
+                "--board-size" => {
+                    let size = args
+                        .next()
+                        .context("--board-size expects a number of cells")?;
+                    if size == "auto" {
+                        settings.board_size = None;
+                        settings.fit_terminal = true;
+                    } else {
+                        let size: u16 = size
+                            .parse()
+                            .with_context(|| format!("Invalid board size {size}"))?;
+                        if !Self::BOARD_SIZES.contains(&size) {
+                            bail!(
+                                "--board-size expects {} to {} cells, or auto",
+                                Self::BOARD_SIZES.start(),
+                                Self::BOARD_SIZES.end()
+                            );
+                        }
+                        settings.board_size = Some(size);
+                        settings.fit_terminal = false;
+                    }
+                }
+                "--growth" => {
+                    let growth = args
+                        .next()
+                        .context("--growth expects a number of segments")?;
+                    settings.growth_per_apple = growth
+                        .parse()
+                        .with_context(|| format!("Invalid growth {growth}"))?;
+                    if !(1..=Self::MAX_GROWTH).contains(&settings.growth_per_apple) {
+                        bail!(
+                            "--growth expects between 1 and {} segments",
+                            Self::MAX_GROWTH
+                        );
+                    }
+                }
+                "--speed-level" => {
+                    let level = args.next().context("--speed-level expects a level")?;
+                    let level = level
+                        .parse()
+                        .with_context(|| format!("Invalid speed level {level}"))?;
+                    if !(1..=Self::MAX_SPEED_LEVEL).contains(&level) {
+                        bail!(
+                            "--speed-level expects a level from 1 to {}",
+                            Self::MAX_SPEED_LEVEL
+                        );
+                    }
+                    settings.speed_level = Some(level);
+                }
+                "--length" => {
+                    let length = args
+                        .next()
+                        .context("--length expects a number of segments")?;
+                    settings.starting_length = length
+                        .parse()
+                        .with_context(|| format!("Invalid length {length}"))?;
+                    if settings.starting_length == 0 {
+                        bail!("--length expects at least one segment");
+                    }
+                }
+                _ => bail!("Unknown argument: {arg}"),
+            }
+        }
+        if settings.growing && settings.maze.is_some() {
+            bail!("--growing can't be used with --map, maps are always the same size");
+        }
+
+        Ok(settings)
+    }
+}
+
+/// What happens when the snake reaches the edge of the board
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BorderMode {
+    /// Hitting the border ends the game
+    #[default]
+    Solid,
+    /// The snake comes out on the opposite edge
+    Wrap,
+    /// The snake turns away from the edge instead of hitting it
+    Bounce,
+}
+
+/// What the player is going for and what ends the game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Play until the snake dies
+    #[default]
+    Classic,
+    /// Score as much as possible before the clock runs out
+    TimeAttack,
+    /// Nothing can kill the snake and the speed stays gentle
+    Zen,
+    /// Faster from the start, with apples hiding along the walls
+    Hardcore,
+    /// The snake leaves a permanent trail and has to survive as long as it can
+    Tron,
+    /// The edges of the board close in as the game goes on
+    Shrinking,
+    /// Lots of apples at a fixed speed, eat as many as possible before the clock runs out
+    Frenzy,
+    /// Apples hop away from the snake when it gets close
+    Evasive,
+    /// Ticks can be rewound and nothing is saved
+    Practice,
+    /// Like the old phone game, at one of nine fixed speeds
+    Nokia,
+    /// Red, yellow, and green apples have to be eaten in that order
+    Sequence,
+    /// Only the head and the tip of the tail can be seen
+    Invisible,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 12] = [
+        Self::Classic,
+        Self::TimeAttack,
+        Self::Zen,
+        Self::Hardcore,
+        Self::Tron,
+        Self::Shrinking,
+        Self::Frenzy,
+        Self::Evasive,
+        Self::Practice,
+        Self::Nokia,
+        Self::Sequence,
+        Self::Invisible,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::TimeAttack => "time-attack",
+            Self::Zen => "zen",
+            Self::Hardcore => "hardcore",
+            Self::Tron => "tron",
+            Self::Shrinking => "shrinking",
+            Self::Frenzy => "frenzy",
+            Self::Evasive => "evasive",
+            Self::Practice => "practice",
+            Self::Nokia => "nokia",
+            Self::Sequence => "sequence",
+            Self::Invisible => "invisible",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// The key that picks the mode on the pre-game screen, the numbers and then any letters that
+    /// aren't already used on it
+    pub fn key(&self) -> char {
+        const KEYS: &str = "1234567890abcdfijklmoprsuvwxyz";
+
+        let index = Self::ALL
+            .iter()
+            .position(|mode| mode == self)
+            .unwrap_or_default();
+        KEYS.chars().nth(index).unwrap_or(' ')
+    }
+
+    pub fn from_key(key: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::TimeAttack => "Time attack: 2 minutes",
+            Self::Zen => "Zen: no walls, no death",
+            Self::Hardcore => "Hardcore",
+            Self::Tron => "Tron: survive your own trail",
+            Self::Shrinking => "Shrinking arena",
+            Self::Frenzy => "Frenzy: 1 minute",
+            Self::Evasive => "Evasive apples",
+            Self::Practice => "Practice: u to rewind",
+            Self::Nokia => "Nokia: pick a speed",
+            Self::Sequence => "Sequence: red, yellow, green",
+            Self::Invisible => "Invisible: remember your body",
+        }
+    }
+}
+
+/// Controls that steer the other way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirror {
+    /// Left and right are swapped
+    LeftRight,
+    /// Every direction is swapped with its opposite
+    All,
+}
+
+impl Mirror {
+    /// The direction the snake actually turns when `direction` is pressed
+    pub fn apply(&self, direction: Direction) -> Direction {
+        match (self, direction) {
+            (Self::LeftRight, Direction::Up | Direction::Down) => direction,
+            _ => direction.opposite(),
+        }
+    }
+}
+
+/// Presets for the size of the board and how fast the snake gets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Easy, Self::Normal, Self::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+    }
+
+    /// Width and height of the board when there's no map
+    pub const fn board_size(&self) -> u16 {
+        match self {
+            Self::Easy | Self::Normal => 20,
+            Self::Hard => 16,
+        }
+    }
+
+    /// Milliseconds between moves at the start of the game
+    pub const fn start_move_time(&self) -> u64 {
+        match self {
+            Self::Easy => 250,
+            Self::Normal => 200,
+            Self::Hard => 150,
+        }
+    }
+
+    /// Milliseconds taken off the move time for every apple
+    pub const fn speed_step(&self) -> u64 {
+        match self {
+            Self::Easy => 5,
+            Self::Normal => 10,
+            Self::Hard => 15,
+        }
+    }
+
+    /// The fastest move time the snake can reach
+    pub const fn min_move_time(&self) -> u64 {
+        match self {
+            Self::Easy => 100,
+            Self::Normal => 50,
+            Self::Hard => 40,
+        }
+    }
+}
+
+/// The rows of the settings screen, each changing one setting for the games after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsRow {
+    StartSpeed,
+    BoardSize,
+    Borders,
+    Combos,
+    Forgiving,
+    Fog,
+    Mines,
+}
+
+impl SettingsRow {
+    pub const ALL: [SettingsRow; 7] = [
+        Self::StartSpeed,
+        Self::BoardSize,
+        Self::Borders,
+        Self::Combos,
+        Self::Forgiving,
+        Self::Fog,
+        Self::Mines,
+    ];
+    const SPEED_STEP: u64 = 10;
+    const BOARD_SIZE_STEP: u16 = 2;
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::StartSpeed => "Start speed",
+            Self::BoardSize => "Board size",
+            Self::Borders => "Borders",
+            Self::Combos => "Combos",
+            Self::Forgiving => "Forgiving",
+            Self::Fog => "Fog",
+            Self::Mines => "Mines",
+        }
+    }
+
+    pub fn value(&self, settings: &Settings) -> String {
+        let toggle = |on: bool| if on { "on" } else { "off" }.to_string();
+        match self {
+            Self::StartSpeed => format!(
+                "{} ms",
+                settings
+                    .start_speed
+                    .unwrap_or_else(|| settings.difficulty.start_move_time())
+            ),
+            Self::BoardSize if settings.fit_terminal => "auto".to_string(),
+            Self::BoardSize => settings.board_size().to_string(),
+            Self::Borders => match settings.border_mode {
+                BorderMode::Solid => "solid",
+                BorderMode::Wrap => "wrap",
+                BorderMode::Bounce => "bounce",
+            }
+            .to_string(),
+            Self::Combos => toggle(settings.combos),
+            Self::Forgiving => toggle(settings.forgiving),
+            Self::Fog => toggle(settings.fog),
+            Self::Mines => toggle(settings.mines),
+        }
+    }
+
+    /// Moves the setting on to its next value, or back to the one before if not `forward`
+    pub fn change(&self, settings: &mut Settings, forward: bool) {
+        match self {
+            // Further right is faster, which is a shorter move time
+            Self::StartSpeed => {
+                let speed = settings
+                    .start_speed
+                    .unwrap_or_else(|| settings.difficulty.start_move_time());
+                let speed = if forward {
+                    speed.saturating_sub(Self::SPEED_STEP)
+                } else {
+                    speed + Self::SPEED_STEP
+                };
+                settings.start_speed = Some(speed.clamp(
+                    *Settings::START_SPEEDS.start(),
+                    *Settings::START_SPEEDS.end(),
+                ));
+            }
+            Self::BoardSize => {
+                let size = settings.board_size();
+                let size = if forward {
+                    size + Self::BOARD_SIZE_STEP
+                } else {
+                    size.saturating_sub(Self::BOARD_SIZE_STEP)
+                };
+                settings.board_size =
+                    Some(size.clamp(*Settings::BOARD_SIZES.start(), *Settings::BOARD_SIZES.end()));
+                settings.fit_terminal = false;
+            }
+            Self::Borders => {
+                settings.border_mode = match (settings.border_mode, forward) {
+                    (BorderMode::Solid, true) | (BorderMode::Bounce, false) => BorderMode::Wrap,
+                    (BorderMode::Wrap, true) | (BorderMode::Solid, false) => BorderMode::Bounce,
+                    (BorderMode::Bounce, true) | (BorderMode::Wrap, false) => BorderMode::Solid,
+                }
+            }
+            Self::Combos => settings.combos = !settings.combos,
+            Self::Forgiving => settings.forgiving = !settings.forgiving,
+            Self::Fog => settings.fog = !settings.fog,
+            Self::Mines => settings.mines = !settings.mines,
+        }
+    }
+}
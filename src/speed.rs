@@ -0,0 +1,109 @@
+/// How the snake speeds up as it eats apples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpeedCurve {
+    /// The same number of milliseconds off for every apple
+    Linear { step: u64 },
+    /// A fraction of the move time off for every apple, gentler the faster the snake gets
+    Exponential { factor: f64 },
+    /// Nothing for a few apples, then a big jump
+    Stepped { every_n_apples: u32, step: u64 },
+}
+
+impl SpeedCurve {
+    pub const NAMES: [&str; 3] = ["linear", "exponential", "stepped"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Self::Linear { step: 10 }),
+            "exponential" => Some(Self::Exponential { factor: 0.93 }),
+            "stepped" => Some(Self::Stepped {
+                every_n_apples: 5,
+                step: 30,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The move time after eating apple number `apples_eaten` at `current`, never going below
+    /// `floor` (or rising if it's already below it)
+    pub fn next_speed(&self, current: u64, apples_eaten: u32, floor: u64) -> u64 {
+        let next = match *self {
+            Self::Linear { step } => current.saturating_sub(step),
+            Self::Exponential { factor } => (current as f64 * factor.clamp(0.0, 1.0)) as u64,
+            Self::Stepped {
+                every_n_apples,
+                step,
+            } if apples_eaten.is_multiple_of(every_n_apples) => current.saturating_sub(step),
+            Self::Stepped { .. } => current,
+        };
+
+        next.max(floor.min(current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 150;
+    const FLOOR: u64 = 70;
+
+    /// The move time after each of the first 30 apples
+    fn speeds(name: &str) -> Vec<u64> {
+        let curve = SpeedCurve::from_name(name).unwrap();
+        let mut current = START;
+        (1..=30)
+            .map(|apples_eaten| {
+                current = curve.next_speed(current, apples_eaten, FLOOR);
+                current
+            })
+            .collect()
+    }
+
+    #[test]
+    fn linear_takes_the_same_off_every_apple() {
+        let mut expected: Vec<u64> = (7..=14).rev().map(|tens| tens * 10).collect();
+        expected.resize(30, FLOOR);
+        assert_eq!(speeds("linear"), expected);
+    }
+
+    #[test]
+    fn exponential_slows_down_as_it_goes() {
+        let mut expected = vec![139, 129, 119, 110, 102, 94, 87, 80, 74];
+        expected.resize(30, FLOOR);
+        assert_eq!(speeds("exponential"), expected);
+    }
+
+    #[test]
+    fn stepped_only_moves_every_few_apples() {
+        let speeds = speeds("stepped");
+        assert_eq!(speeds[..4], [START; 4]);
+        assert_eq!(speeds[4..9], [120; 5]);
+        assert_eq!(speeds[9..14], [90; 5]);
+        assert!(speeds[14..].iter().all(|&speed| speed == FLOOR));
+    }
+
+    #[test]
+    fn floor_never_speeds_up_a_slower_snake() {
+        for name in SpeedCurve::NAMES {
+            let curve = SpeedCurve::from_name(name).unwrap();
+            assert_eq!(curve.next_speed(75, 5, FLOOR), FLOOR, "{name}");
+            assert_eq!(curve.next_speed(FLOOR, 5, FLOOR), FLOOR, "{name}");
+            // Already under the floor, from a speed boost say
+            assert_eq!(curve.next_speed(50, 5, FLOOR), 50, "{name}");
+        }
+    }
+
+    #[test]
+    fn big_steps_dont_underflow() {
+        let linear = SpeedCurve::Linear { step: 100 };
+        assert_eq!(linear.next_speed(30, 1, 0), 0);
+        let stepped = SpeedCurve::Stepped {
+            every_n_apples: 1,
+            step: 100,
+        };
+        assert_eq!(stepped.next_speed(30, 1, 0), 0);
+        let exponential = SpeedCurve::Exponential { factor: -1.0 };
+        assert_eq!(exponential.next_speed(30, 1, 0), 0);
+    }
+}
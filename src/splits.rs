@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::storage;
+
+const FILE_NAME: &str = "splits.txt";
+
+/// Points between each split
+pub const SPLIT_SCORE: u32 = 10;
+
+/// The fastest time to reach each split that has been saved for the `bucket` of scores for the
+/// game's rules, in order
+pub fn best_splits(bucket: &str) -> Vec<Duration> {
+    storage::read(FILE_NAME)
+        .lines()
+        .find_map(|line| {
+            let (line_bucket, splits) = line.split_once(' ')?;
+            (line_bucket == bucket).then(|| {
+                splits
+                    .split_whitespace()
+                    .map_while(|millis| millis.parse().ok().map(Duration::from_millis))
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Saves `splits` as the best for `bucket`, replacing whatever was there
+pub fn save_best_splits(bucket: &str, splits: &[Duration]) -> Result<()> {
+    let mut lines: Vec<_> = storage::read(FILE_NAME)
+        .lines()
+        .filter(|line| {
+            line.split_once(' ')
+                .is_none_or(|(line_bucket, _)| line_bucket != bucket)
+        })
+        .map(str::to_string)
+        .collect();
+    let millis: Vec<_> = splits
+        .iter()
+        .map(|split| split.as_millis().to_string())
+        .collect();
+    lines.push(format!("{bucket} {}", millis.join(" ")));
+
+    storage::write(FILE_NAME, &(lines.join("\n") + "\n"))
+}
+
+/// The best of both lists at each split, taking whichever has one where the other doesn't
+pub fn merge(best: &[Duration], run: &[Duration]) -> Vec<Duration> {
+    (0..best.len().max(run.len()))
+        .map(|index| match (best.get(index), run.get(index)) {
+            (Some(best), Some(run)) => *best.min(run),
+            (Some(split), None) | (None, Some(split)) => *split,
+            (None, None) => Duration::ZERO,
+        })
+        .collect()
+}
@@ -0,0 +1,36 @@
+use std::{env, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Returns where `file_name` is kept between runs, in `$XDG_DATA_HOME/terminal_snake` or
+/// `~/.local/share/terminal_snake`
+pub fn path(file_name: &str) -> Option<PathBuf> {
+    let data_dir = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(data_dir.join("terminal_snake").join(file_name))
+}
+
+/// Reads a saved file, treating one that doesn't exist yet as empty
+pub fn read(file_name: &str) -> String {
+    path(file_name)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+/// Replaces a saved file with `contents`. It's written alongside first and then moved into
+/// place, so a crash partway through leaves the old file as it was
+pub fn write(file_name: &str, contents: &str) -> Result<()> {
+    let path = path(file_name).context("Couldn't find a directory to save to")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Couldn't create {}", dir.display()))?;
+    }
+
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, contents)
+        .with_context(|| format!("Couldn't write {}", temp_path.display()))?;
+    fs::rename(&temp_path, &path).with_context(|| format!("Couldn't replace {}", path.display()))
+}
@@ -0,0 +1,112 @@
+use crossterm::event::KeyCode;
+
+use crate::{
+    Direction,
+    glyphs::Glyphs,
+    keymap::{Action, Keymap},
+};
+
+/// Something the tutorial asks the player to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Turn(Direction),
+    EatApple,
+    Sprint,
+    Brake,
+}
+
+impl Step {
+    pub fn instruction(&self, keymap: &Keymap, glyphs: &Glyphs) -> String {
+        let (action, what) = match self {
+            Self::Turn(Direction::Up) => (Action::Up, "turn up"),
+            Self::Turn(Direction::Down) => (Action::Down, "turn down"),
+            Self::Turn(Direction::Left) => (Action::Left, "turn left"),
+            Self::Turn(Direction::Right) => (Action::Right, "turn right"),
+            Self::EatApple => return "Eat the apple".to_string(),
+            Self::Sprint => (Action::Sprint, "sprint"),
+            Self::Brake => (Action::Brake, "brake"),
+        };
+        format!("Press {} to {what}", keymap.key_name(action, glyphs))
+    }
+}
+
+/// Walks a new player through the controls one step at a time
+#[derive(Debug, Default)]
+pub struct Tutorial {
+    step: usize,
+}
+
+impl Tutorial {
+    const STEPS: [Step; 5] = [
+        Step::Turn(Direction::Up),
+        Step::Turn(Direction::Right),
+        Step::EatApple,
+        Step::Sprint,
+        Step::Brake,
+    ];
+
+    /// The step the player is on, or `None` once they've done them all
+    pub fn step(&self) -> Option<Step> {
+        Self::STEPS.get(self.step).copied()
+    }
+
+    /// Whether the snake holds still until the player does what the step asks
+    pub fn waiting(&self) -> bool {
+        self.step().is_some_and(|step| step != Step::EatApple)
+    }
+
+    /// Moves on to the next step if `code` is the key the current one asks for, returning
+    /// whether it did
+    pub fn press(&mut self, code: KeyCode, keymap: &Keymap) -> bool {
+        let done = match self.step() {
+            Some(Step::Turn(direction)) => keymap.direction(code) == Some(direction),
+            Some(Step::Sprint) => keymap.action(code) == Some(Action::Sprint),
+            Some(Step::Brake) => keymap.action(code) == Some(Action::Brake),
+            Some(Step::EatApple) | None => false,
+        };
+        if done {
+            self.step += 1;
+        }
+
+        done
+    }
+
+    /// Moves on from the apple step once the apple has been eaten
+    pub fn ate_apple(&mut self) {
+        if self.step() == Some(Step::EatApple) {
+            self.step += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instructions_follow_the_keys_file() {
+        let keymap = Keymap::parse("up = i Up\nsprint = x").unwrap();
+        let instruction = |step: Step| step.instruction(&keymap, &Glyphs::ASCII);
+        assert_eq!(instruction(Step::Turn(Direction::Up)), "Press i to turn up");
+        assert_eq!(
+            instruction(Step::Turn(Direction::Left)),
+            "Press < to turn left"
+        );
+        assert_eq!(instruction(Step::Sprint), "Press x to sprint");
+        assert_eq!(instruction(Step::Brake), "Press b to brake");
+    }
+
+    #[test]
+    fn steps_wait_for_the_bound_key() {
+        let keymap = Keymap::parse("up = i\nsprint = x").unwrap();
+        let mut tutorial = Tutorial::default();
+        assert!(!tutorial.press(KeyCode::Up, &keymap));
+        assert!(tutorial.press(KeyCode::Char('i'), &keymap));
+        assert!(tutorial.press(KeyCode::Right, &keymap));
+        tutorial.ate_apple();
+        assert!(!tutorial.press(KeyCode::Char(' '), &keymap));
+        assert!(tutorial.press(KeyCode::Char('x'), &keymap));
+        assert!(tutorial.press(KeyCode::Char('b'), &keymap));
+        assert_eq!(tutorial.step(), None);
+    }
+}